@@ -0,0 +1,199 @@
+use std::str::FromStr;
+
+// A claim covers the half-open rectangle [x, x + width) x [y, y + height), so two
+// claims that merely touch along an edge do not overlap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Claim {
+    pub id: usize,
+    pub x: u64,
+    pub y: u64,
+    pub width: u64,
+    pub height: u64,
+}
+
+impl Claim {
+    pub fn right(&self) -> u64 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> u64 {
+        self.y + self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    pub fn overlaps(&self, other: &Claim) -> bool {
+        !self.is_empty()
+            && !other.is_empty()
+            && self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ClaimError {
+    Malformed(String),
+    OutOfRange(String),
+}
+
+impl FromStr for Claim {
+    type Err = ClaimError;
+
+    // Parses lines of the form "#123 @ 3,2: 5x4"
+    fn from_str(s: &str) -> Result<Claim, ClaimError> {
+        let malformed = || ClaimError::Malformed(s.to_owned());
+        let number = |t: &str| t.trim().parse::<u64>().map_err(|_| malformed());
+
+        let rest = s.trim().strip_prefix('#').ok_or_else(malformed)?;
+        let (id, rest) = rest.split_once('@').ok_or_else(malformed)?;
+        let (corner, size) = rest.split_once(':').ok_or_else(malformed)?;
+        let (x, y) = corner.split_once(',').ok_or_else(malformed)?;
+        let (width, height) = size.split_once('x').ok_or_else(malformed)?;
+
+        let claim = Claim {
+            id: id.trim().parse().map_err(|_| malformed())?,
+            x: number(x)?,
+            y: number(y)?,
+            width: number(width)?,
+            height: number(height)?,
+        };
+        // right() and bottom() must not overflow
+        if claim.x.checked_add(claim.width).is_none() || claim.y.checked_add(claim.height).is_none()
+        {
+            return Err(ClaimError::OutOfRange(s.to_owned()));
+        }
+        Ok(claim)
+    }
+}
+
+// Total area covered by two or more claims.
+//
+// The x axis is compressed to the distinct claim edges. Between two consecutive
+// edges the set of active claims is constant, so for each such slab we sweep the
+// y edges of the active claims and measure the length covered at least twice.
+pub fn overlap_area(claims: &[Claim]) -> u128 {
+    let mut xs: Vec<u64> = claims
+        .iter()
+        .filter(|c| !c.is_empty())
+        .flat_map(|c| vec![c.x, c.right()])
+        .collect();
+    xs.sort_unstable();
+    xs.dedup();
+
+    let mut by_left: Vec<&Claim> = claims.iter().filter(|c| !c.is_empty()).collect();
+    by_left.sort_by_key(|c| c.x);
+    let mut pending = by_left.into_iter().peekable();
+    let mut active: Vec<&Claim> = Vec::new();
+
+    let mut area = 0u128;
+    for slab in xs.windows(2) {
+        let (x0, x1) = (slab[0], slab[1]);
+        active.retain(|c| c.right() > x0);
+        while let Some(c) = pending.next_if(|c| c.x <= x0) {
+            active.push(c);
+        }
+
+        // Closing edges sort before opening edges at the same y; either order gives
+        // the same length, but this keeps the depth from briefly overshooting.
+        let mut edges: Vec<(u64, i32)> = active
+            .iter()
+            .flat_map(|c| vec![(c.y, 1), (c.bottom(), -1)])
+            .collect();
+        edges.sort_unstable();
+
+        let mut depth = 0;
+        let mut covered = 0u128;
+        let mut last_y = 0;
+        for (y, delta) in edges {
+            if depth >= 2 {
+                covered += u128::from(y - last_y);
+            }
+            depth += delta;
+            last_y = y;
+        }
+        area += covered * u128::from(x1 - x0);
+    }
+    area
+}
+
+// For every claim (by index into `claims`), the sorted indices of all claims it
+// overlaps with.
+pub fn overlap_graph(claims: &[Claim]) -> Vec<Vec<usize>> {
+    let mut graph = vec![Vec::new(); claims.len()];
+
+    let mut by_left: Vec<usize> = (0..claims.len())
+        .filter(|&i| !claims[i].is_empty())
+        .collect();
+    by_left.sort_by_key(|&i| claims[i].x);
+
+    // Claims are added in order of their left edge; anything whose right edge lies
+    // at or before the current left edge can never overlap a later claim.
+    let mut active: Vec<usize> = Vec::new();
+    for i in by_left {
+        let current = &claims[i];
+        active.retain(|&j| claims[j].right() > current.x);
+        for &j in &active {
+            if current.overlaps(&claims[j]) {
+                graph[i].push(j);
+                graph[j].push(i);
+            }
+        }
+        active.push(i);
+    }
+
+    for neighbours in graph.iter_mut() {
+        neighbours.sort_unstable();
+    }
+    graph
+}
+
+// The ids of all claims that do not overlap any other claim.
+pub fn non_overlapping(claims: &[Claim]) -> Vec<usize> {
+    overlap_graph(claims)
+        .iter()
+        .zip(claims)
+        .filter(|(neighbours, _)| neighbours.is_empty())
+        .map(|(_, c)| c.id)
+        .collect()
+}
+
+#[cfg(test)]
+fn parse(lines: &[&str]) -> Vec<Claim> {
+    lines.iter().map(|l| l.parse().unwrap()).collect()
+}
+
+#[test]
+fn test() {
+    let claims = parse(&["#1 @ 1,3: 4x4", "#2 @ 3,1: 4x4", "#3 @ 5,5: 2x2"]);
+    assert_eq!(overlap_area(&claims), 4);
+    assert_eq!(overlap_graph(&claims), vec![vec![1], vec![0], vec![]]);
+    assert_eq!(non_overlapping(&claims), vec![3]);
+}
+
+#[test]
+fn test_touching_and_large() {
+    // #1 and #2 share an edge, #3 shares only a corner with #1
+    let claims = parse(&["#1 @ 0,0: 2x2", "#2 @ 2,0: 2x2", "#3 @ 2,2: 1x1"]);
+    assert_eq!(overlap_area(&claims), 0);
+    assert_eq!(non_overlapping(&claims), vec![1, 2, 3]);
+
+    let claims = parse(&[
+        "#7 @ 0,0: 4000000000x4000000000",
+        "#8 @ 1000000000,1000000000: 4000000000x4000000000",
+        "#9 @ 3000000000,3000000000: 1x0",
+    ]);
+    assert_eq!(overlap_area(&claims), 3_000_000_000u128 * 3_000_000_000);
+    assert_eq!(overlap_graph(&claims), vec![vec![1], vec![0], vec![]]);
+
+    assert_eq!(
+        "#1 @ 18446744073709551615,0: 1x1".parse::<Claim>(),
+        Err(ClaimError::OutOfRange(
+            "#1 @ 18446744073709551615,0: 1x1".to_owned()
+        ))
+    );
+    assert!("#1 @ 1,2 3x4".parse::<Claim>().is_err());
+}
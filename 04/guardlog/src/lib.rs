@@ -0,0 +1,415 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+pub mod render;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Timestamp {
+    pub fn days_in_month(year: i64, month: u32) -> u32 {
+        match month {
+            2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    // Minutes since 0000-03-01 (proleptic Gregorian), so that differences between
+    // timestamps are correct across days, months and years.
+    pub fn absolute_minutes(&self) -> i64 {
+        let y = if self.month <= 2 {
+            self.year - 1
+        } else {
+            self.year
+        };
+        let era = y.div_euclid(400);
+        let year_of_era = y - era * 400;
+        let month_from_march = (i64::from(self.month) + 9) % 12;
+        let day_of_year = (153 * month_from_march + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era;
+        (days * 24 + i64::from(self.hour)) * 60 + i64::from(self.minute)
+    }
+
+    // The inverse of absolute_minutes
+    pub fn from_absolute_minutes(minutes: i64) -> Timestamp {
+        let days = minutes.div_euclid(24 * 60);
+        let minute_of_day = minutes.rem_euclid(24 * 60);
+        let era = days.div_euclid(146_097);
+        let day_of_era = days - era * 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+        let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+        Timestamp {
+            year,
+            month: month as u32,
+            day: (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32,
+            hour: (minute_of_day / 60) as u32,
+            minute: (minute_of_day % 60) as u32,
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventKind {
+    BeginsShift(usize),
+    FallsAsleep,
+    WakesUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShiftEvent {
+    pub time: Timestamp,
+    pub kind: EventKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogError {
+    Malformed(String),
+    NoGuardOnDuty(Timestamp),
+    AlreadyAsleep(Timestamp),
+    NotAsleep(Timestamp),
+    // The guard, and when they fell asleep without waking up before the next shift
+    // or the end of the log
+    StillAsleep(usize, Timestamp),
+}
+
+impl FromStr for ShiftEvent {
+    type Err = LogError;
+
+    // Parses lines of the form "[1518-11-01 23:58] Guard #99 begins shift"
+    fn from_str(s: &str) -> Result<ShiftEvent, LogError> {
+        let malformed = || LogError::Malformed(s.to_owned());
+
+        let rest = s.trim().strip_prefix('[').ok_or_else(malformed)?;
+        let (stamp, action) = rest.split_once("] ").ok_or_else(malformed)?;
+        let (date, clock) = stamp.split_once(' ').ok_or_else(malformed)?;
+        let mut date = date.splitn(3, '-');
+        let (hour, minute) = clock.split_once(':').ok_or_else(malformed)?;
+
+        let time = Timestamp {
+            year: number(date.next().ok_or_else(malformed)?, s)?,
+            month: number(date.next().ok_or_else(malformed)?, s)?,
+            day: number(date.next().ok_or_else(malformed)?, s)?,
+            hour: number(hour, s)?,
+            minute: number(minute, s)?,
+        };
+        if !(1..=12).contains(&time.month)
+            || !(1..=Timestamp::days_in_month(time.year, time.month)).contains(&time.day)
+            || time.hour >= 24
+            || time.minute >= 60
+        {
+            return Err(malformed());
+        }
+
+        let kind = match action {
+            "falls asleep" => EventKind::FallsAsleep,
+            "wakes up" => EventKind::WakesUp,
+            _ => EventKind::BeginsShift(
+                action
+                    .strip_prefix("Guard #")
+                    .and_then(|a| a.strip_suffix(" begins shift"))
+                    .ok_or_else(malformed)
+                    .and_then(|id| number(id, s))?,
+            ),
+        };
+        Ok(ShiftEvent { time, kind })
+    }
+}
+
+fn number<T: FromStr>(t: &str, line: &str) -> Result<T, LogError> {
+    t.parse().map_err(|_| LogError::Malformed(line.to_owned()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SleepInterval {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl SleepInterval {
+    // The minutes of the midnight hour (00:00 - 00:59) covered by this interval,
+    // which may span several days or start before midnight.
+    pub fn midnight_minutes(&self) -> impl Iterator<Item = usize> {
+        self.midnight_times().map(|t| t.minute as usize)
+    }
+
+    // As midnight_minutes, but keeping the full timestamp of every minute
+    pub fn midnight_times(&self) -> impl Iterator<Item = Timestamp> {
+        (self.start.absolute_minutes()..self.end.absolute_minutes())
+            .filter(|t| t.rem_euclid(24 * 60) < 60)
+            .map(Timestamp::from_absolute_minutes)
+    }
+}
+
+// One guard's shift: when it began, and the intervals they slept through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shift {
+    pub guard: usize,
+    pub start: Timestamp,
+    pub sleep: Vec<SleepInterval>,
+}
+
+impl Shift {
+    // Midnight at the start of the night this shift covers. Shifts that begin
+    // before the midnight hour cover the next night.
+    pub fn night(&self) -> Timestamp {
+        let day = 24 * 60;
+        let start = self.start.absolute_minutes();
+        Timestamp::from_absolute_minutes((start + day - 60).div_euclid(day) * day)
+    }
+}
+
+// Sorts the events chronologically and collects the shifts, in order. Events sharing
+// a timestamp keep their input order.
+pub fn shifts(events: &[ShiftEvent]) -> Result<Vec<Shift>, LogError> {
+    let mut events = events.to_vec();
+    events.sort_by_key(|e| e.time);
+
+    let mut shifts: Vec<Shift> = Vec::new();
+    let mut asleep_since = None;
+    for e in events {
+        match e.kind {
+            EventKind::BeginsShift(guard) => {
+                if let (Some(previous), Some(since)) = (shifts.last(), asleep_since) {
+                    return Err(LogError::StillAsleep(previous.guard, since));
+                }
+                shifts.push(Shift {
+                    guard,
+                    start: e.time,
+                    sleep: Vec::new(),
+                });
+            }
+            EventKind::FallsAsleep => {
+                if shifts.is_empty() {
+                    return Err(LogError::NoGuardOnDuty(e.time));
+                }
+                if asleep_since.is_some() {
+                    return Err(LogError::AlreadyAsleep(e.time));
+                }
+                asleep_since = Some(e.time);
+            }
+            EventKind::WakesUp => {
+                let shift = shifts.last_mut().ok_or(LogError::NoGuardOnDuty(e.time))?;
+                let start = asleep_since.take().ok_or(LogError::NotAsleep(e.time))?;
+                shift.sleep.push(SleepInterval { start, end: e.time });
+            }
+        }
+    }
+    if let (Some(shift), Some(since)) = (shifts.last(), asleep_since) {
+        return Err(LogError::StillAsleep(shift.guard, since));
+    }
+    Ok(shifts)
+}
+
+// Sleep intervals per guard id
+pub type SleepTimes = BTreeMap<usize, Vec<SleepInterval>>;
+
+// The sleep intervals of every guard, from all their shifts
+pub fn sleep_times(events: &[ShiftEvent]) -> Result<SleepTimes, LogError> {
+    let mut sleep_times = BTreeMap::new();
+    for shift in shifts(events)? {
+        sleep_times
+            .entry(shift.guard)
+            .or_insert_with(Vec::new)
+            .extend(shift.sleep);
+    }
+    Ok(sleep_times)
+}
+
+// How often each guard was asleep on each minute of the midnight hour.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap(pub BTreeMap<usize, [u32; 60]>);
+
+impl Heatmap {
+    pub fn new(sleep_times: &SleepTimes) -> Heatmap {
+        Heatmap(
+            sleep_times
+                .iter()
+                .map(|(&guard, intervals)| {
+                    let mut minutes = [0; 60];
+                    for m in intervals.iter().flat_map(SleepInterval::midnight_minutes) {
+                        minutes[m] += 1;
+                    }
+                    (guard, minutes)
+                })
+                .collect(),
+        )
+    }
+
+    // The minute this guard was most often asleep, with the count. Ties go to the
+    // earliest minute.
+    pub fn sleepiest_minute(&self, guard: usize) -> Option<(usize, u32)> {
+        let minutes = self.0.get(&guard)?;
+        let (minute, &count) = minutes
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|&(_, count)| count)?;
+        Some((minute, count))
+    }
+
+    pub fn total_asleep(&self, guard: usize) -> u32 {
+        self.0.get(&guard).map_or(0, |minutes| minutes.iter().sum())
+    }
+
+    // Strategy 1: the guard with the most minutes asleep, and their sleepiest minute.
+    // None if nobody ever slept.
+    pub fn most_asleep(&self) -> Option<(usize, usize)> {
+        let guard = *self
+            .0
+            .keys()
+            .rev()
+            .filter(|&&guard| self.total_asleep(guard) > 0)
+            .max_by_key(|&&guard| self.total_asleep(guard))?;
+        self.sleepiest_minute(guard)
+            .map(|(minute, _)| (guard, minute))
+    }
+
+    // Strategy 2: the guard and minute with the highest sleep count. None if nobody
+    // ever slept.
+    pub fn most_frequent(&self) -> Option<(usize, usize)> {
+        self.0
+            .keys()
+            .rev()
+            .filter_map(|&guard| Some((guard, self.sleepiest_minute(guard)?)))
+            .filter(|&(_, (_, count))| count > 0)
+            .max_by_key(|&(_, (_, count))| count)
+            .map(|(guard, (minute, _))| (guard, minute))
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:05] falls asleep
+[1518-11-01 00:25] wakes up
+[1518-11-01 00:30] falls asleep
+[1518-11-01 00:55] wakes up
+[1518-11-01 23:58] Guard #99 begins shift
+[1518-11-02 00:40] falls asleep
+[1518-11-02 00:50] wakes up
+[1518-11-03 00:05] Guard #10 begins shift
+[1518-11-03 00:24] falls asleep
+[1518-11-03 00:29] wakes up
+[1518-11-04 00:02] Guard #99 begins shift
+[1518-11-04 00:36] falls asleep
+[1518-11-04 00:46] wakes up
+[1518-11-05 00:03] Guard #99 begins shift
+[1518-11-05 00:45] falls asleep
+[1518-11-05 00:55] wakes up";
+
+#[cfg(test)]
+fn parse(log: &str) -> Vec<ShiftEvent> {
+    log.lines().map(|l| l.parse().unwrap()).collect()
+}
+
+#[test]
+fn test() {
+    // Shuffle the example to make sure the order of the input doesn't matter
+    let mut events = parse(EXAMPLE);
+    events.reverse();
+    events.swap(3, 11);
+
+    let heatmap = Heatmap::new(&sleep_times(&events).unwrap());
+    assert_eq!(heatmap.total_asleep(10), 50);
+    assert_eq!(heatmap.most_asleep(), Some((10, 24)));
+    assert_eq!(heatmap.most_frequent(), Some((99, 45)));
+
+    // Guards who stay awake have no sleepiest minute to speak of
+    let events = parse("[1518-11-01 00:00] Guard #10 begins shift");
+    let heatmap = Heatmap::new(&sleep_times(&events).unwrap());
+    assert_eq!(heatmap.most_asleep(), None);
+    assert_eq!(heatmap.most_frequent(), None);
+}
+
+#[test]
+fn test_validation() {
+    for line in EXAMPLE.lines() {
+        let t = line.parse::<ShiftEvent>().unwrap().time;
+        assert_eq!(Timestamp::from_absolute_minutes(t.absolute_minutes()), t);
+    }
+
+    let events = parse(
+        "[1518-12-31 23:50] Guard #7 begins shift
+[1518-12-31 23:58] falls asleep
+[1519-01-01 00:03] wakes up",
+    );
+    let heatmap = Heatmap::new(&sleep_times(&events).unwrap());
+    assert_eq!(heatmap.total_asleep(7), 3);
+    assert_eq!(heatmap.sleepiest_minute(7), Some((0, 1)));
+
+    let stamp = |day, minute| Timestamp {
+        year: 1518,
+        month: 3,
+        day,
+        hour: 0,
+        minute,
+    };
+    let events = parse("[1518-03-01 00:10] falls asleep");
+    assert_eq!(
+        sleep_times(&events),
+        Err(LogError::NoGuardOnDuty(stamp(1, 10)))
+    );
+    let events = parse(
+        "[1518-03-01 00:00] Guard #1 begins shift
+[1518-03-01 00:10] falls asleep
+[1518-03-01 00:20] falls asleep",
+    );
+    assert_eq!(
+        sleep_times(&events),
+        Err(LogError::AlreadyAsleep(stamp(1, 20)))
+    );
+    let events = parse(
+        "[1518-03-01 00:00] Guard #1 begins shift
+[1518-03-01 00:10] wakes up",
+    );
+    assert_eq!(sleep_times(&events), Err(LogError::NotAsleep(stamp(1, 10))));
+    let events = parse(
+        "[1518-03-01 00:00] Guard #1 begins shift
+[1518-03-01 00:10] falls asleep
+[1518-03-02 00:00] Guard #2 begins shift",
+    );
+    assert_eq!(
+        sleep_times(&events),
+        Err(LogError::StillAsleep(1, stamp(1, 10)))
+    );
+    let events = parse(
+        "[1518-03-01 00:00] Guard #1 begins shift
+[1518-03-01 00:10] falls asleep",
+    );
+    assert_eq!(
+        sleep_times(&events),
+        Err(LogError::StillAsleep(1, stamp(1, 10)))
+    );
+    assert!("[1518-03-01 00:60] wakes up".parse::<ShiftEvent>().is_err());
+    assert!("[1518-02-31 00:10] wakes up".parse::<ShiftEvent>().is_err());
+    assert!("[1518-02-29 00:10] wakes up".parse::<ShiftEvent>().is_err());
+    assert!("[1518-04-31 00:10] wakes up".parse::<ShiftEvent>().is_err());
+    assert!("[1520-02-29 00:10] wakes up".parse::<ShiftEvent>().is_ok());
+}
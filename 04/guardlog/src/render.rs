@@ -0,0 +1,140 @@
+use crate::{Heatmap, Shift};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+// The table from the puzzle description: one row per night and guard, with a '#'
+// for every minute of the midnight hour that guard was asleep.
+//
+// Date   ID   Minute
+//             000000000011111111112222222222333333333344444444445555555555
+//             012345678901234567890123456789012345678901234567890123456789
+// 11-01  #10  .....####################.....#########################.....
+//
+// Shifts in which the guard never fell asleep get a row of dots. Sleep that lasts
+// into a later night shows up in a row for that night as well.
+pub fn timeline(shifts: &[Shift]) -> String {
+    let mut nights = BTreeMap::new();
+    for shift in shifts {
+        let night = shift.night();
+        nights
+            .entry((night.year, night.month, night.day, shift.guard))
+            .or_insert([false; 60]);
+        for t in shift.sleep.iter().flat_map(|i| i.midnight_times()) {
+            nights
+                .entry((t.year, t.month, t.day, shift.guard))
+                .or_insert([false; 60])[t.minute as usize] = true;
+        }
+    }
+
+    let id_width = shifts
+        .iter()
+        .map(|shift| shift.guard.to_string().len() + 1)
+        .max()
+        .unwrap_or(0)
+        .max(2);
+    let indent = " ".repeat(5 + 2 + id_width + 2);
+
+    let mut out = String::new();
+    writeln!(out, "Date   {:width$}  Minute", "ID", width = id_width).unwrap();
+    out.push_str(&minute_axis(&indent));
+    for ((_, month, day, guard), asleep) in nights {
+        let minutes: String = asleep.iter().map(|&a| if a { '#' } else { '.' }).collect();
+        let id = format!("#{}", guard);
+        writeln!(
+            out,
+            "{:02}-{:02}  {:width$}  {}",
+            month,
+            day,
+            id,
+            minutes,
+            width = id_width
+        )
+        .unwrap();
+    }
+    out
+}
+
+// A vertical bar chart per guard of how many nights they slept on each minute.
+pub fn histogram(heatmap: &Heatmap) -> String {
+    let mut out = String::new();
+    for (&guard, minutes) in &heatmap.0 {
+        writeln!(
+            out,
+            "Guard #{} ({} minutes asleep)",
+            guard,
+            heatmap.total_asleep(guard)
+        )
+        .unwrap();
+        let highest = minutes.iter().cloned().max().unwrap_or(0);
+        for level in (1..=highest).rev() {
+            let bars: String = minutes
+                .iter()
+                .map(|&count| if count >= level { '#' } else { ' ' })
+                .collect();
+            writeln!(out, "{:3} |{}", level, bars.trim_end()).unwrap();
+        }
+        writeln!(out, "    +{}", "-".repeat(60)).unwrap();
+        out.push_str(&minute_axis("     "));
+        writeln!(out).unwrap();
+    }
+    out
+}
+
+// The minute-by-guard matrix as CSV: a header row "guard,0,1,...,59", then one
+// row per guard with the number of nights they were asleep on each minute.
+pub fn csv(heatmap: &Heatmap) -> String {
+    let mut out = String::from("guard");
+    for m in 0..60 {
+        write!(out, ",{}", m).unwrap();
+    }
+    out.push('\n');
+    for (guard, minutes) in &heatmap.0 {
+        write!(out, "{}", guard).unwrap();
+        for count in minutes.iter() {
+            write!(out, ",{}", count).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+// Two lines numbering the minutes 00 to 59 vertically
+fn minute_axis(indent: &str) -> String {
+    let tens: String = (0..60).map(|m| (b'0' + m / 10) as char).collect();
+    let ones: String = (0..60).map(|m| (b'0' + m % 10) as char).collect();
+    format!("{}{}\n{}{}\n", indent, tens, indent, ones)
+}
+
+#[test]
+fn test() {
+    let shifts = crate::shifts(&crate::parse(crate::EXAMPLE)).unwrap();
+    assert_eq!(
+        timeline(&shifts),
+        "Date   ID   Minute
+            000000000011111111112222222222333333333344444444445555555555
+            012345678901234567890123456789012345678901234567890123456789
+11-01  #10  .....####################.....#########################.....
+11-02  #99  ........................................##########..........
+11-03  #10  ........................#####...............................
+11-04  #99  ....................................##########..............
+11-05  #99  .............................................##########.....
+"
+    );
+
+    // A guard who stays awake all night still has a row
+    let log = crate::EXAMPLE.to_owned() + "\n[1518-11-05 23:59] Guard #7 begins shift";
+    let shifts = crate::shifts(&crate::parse(&log)).unwrap();
+    assert_eq!(shifts[5].night().day, 6);
+    assert!(timeline(&shifts).ends_with(&format!("11-06  #7   {}\n", ".".repeat(60))));
+
+    let sleep_times = crate::sleep_times(&crate::parse(crate::EXAMPLE)).unwrap();
+    let heatmap = Heatmap::new(&sleep_times);
+    let csv = csv(&heatmap);
+    let mut rows = csv.lines();
+    assert!(rows.next().unwrap().starts_with("guard,0,1,2,"));
+    assert_eq!(rows.next().unwrap().split(',').nth(24 + 1), Some("2"));
+    assert_eq!(rows.next().unwrap().split(',').nth(45 + 1), Some("3"));
+    assert_eq!(rows.next(), None);
+
+    assert!(histogram(&heatmap).contains("Guard #99 (30 minutes asleep)\n  3 |"));
+}
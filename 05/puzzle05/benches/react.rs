@@ -0,0 +1,38 @@
+use puzzle05::{best_removal, react, AsciiCase};
+use std::time::Instant;
+
+// Run with `cargo bench`. Reacts a pseudo-random polymer of several megabytes,
+// then tries every removal on the result.
+fn main() {
+    const SIZE: usize = 8 << 20;
+
+    // A small xorshift generator keeps the polymer reproducible without extra crates.
+    // Only a handful of letters are used, so that plenty of units react.
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let polymer: Vec<char> = (0..SIZE)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let letter = (b'a' + (state % 6) as u8) as char;
+            if state & 64 == 0 {
+                letter.to_ascii_uppercase()
+            } else {
+                letter
+            }
+        })
+        .collect();
+
+    let start = Instant::now();
+    let reduced = react(AsciiCase, polymer.iter().cloned());
+    println!(
+        "react: {} units -> {} units in {:?}",
+        polymer.len(),
+        reduced.len(),
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let best = best_removal(&AsciiCase, &reduced);
+    println!("best_removal: {:?} in {:?}", best, start.elapsed());
+}
@@ -0,0 +1,274 @@
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+// Decides which pairs of adjacent units annihilate each other.
+pub trait ReactionRule {
+    fn reacts(&self, a: char, b: char) -> bool;
+
+    // Removing a unit type removes every unit of that type. Units that react with
+    // each other should share a type.
+    fn unit_type(&self, unit: char) -> char {
+        unit
+    }
+
+    // Whether a polymer always reduces to the same result, whatever the order in
+    // which the reactions happen. Rules are assumed not to be, unless they say so.
+    fn confluent(&self) -> bool {
+        false
+    }
+}
+
+impl<R: ReactionRule> ReactionRule for &R {
+    fn reacts(&self, a: char, b: char) -> bool {
+        (*self).reacts(a, b)
+    }
+
+    fn unit_type(&self, unit: char) -> char {
+        (*self).unit_type(unit)
+    }
+
+    fn confluent(&self) -> bool {
+        (*self).confluent()
+    }
+}
+
+// The puzzle's rule: the same letter in a different case, ASCII only
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsciiCase;
+
+impl ReactionRule for AsciiCase {
+    fn reacts(&self, a: char, b: char) -> bool {
+        a != b && a.eq_ignore_ascii_case(&b)
+    }
+
+    fn unit_type(&self, unit: char) -> char {
+        unit.to_ascii_lowercase()
+    }
+
+    // A unit only reacts with its twin in the other case, so when x reacts with y
+    // and y with z, x and z are the same and xyz leaves the same unit either way
+    fn confluent(&self) -> bool {
+        true
+    }
+}
+
+// As AsciiCase, but using Unicode case folding, so that e.g. 'ä' and 'Ä' react.
+// This is not confluent: 'K', 'k' and the Kelvin sign 'K' all react with each other.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UnicodeCase;
+
+impl ReactionRule for UnicodeCase {
+    fn reacts(&self, a: char, b: char) -> bool {
+        a != b && a.to_lowercase().eq(b.to_lowercase())
+    }
+
+    fn unit_type(&self, unit: char) -> char {
+        // Some chars, like 'İ', lowercase to several chars; those are left alone
+        let mut lower = unit.to_lowercase();
+        match (lower.next(), lower.next()) {
+            (Some(l), None) => l,
+            _ => unit,
+        }
+    }
+}
+
+// An explicit table of reacting pairs, e.g. read from a rules file. Units that are
+// connected through reacting pairs share a unit type, named after the smallest of them.
+#[derive(Debug, Default, Clone)]
+pub struct PairTable {
+    pairs: HashSet<(char, char)>,
+    types: HashMap<char, char>,
+}
+
+impl PairTable {
+    pub fn new<I: IntoIterator<Item = (char, char)>>(pairs: I) -> PairTable {
+        let mut table = PairTable::default();
+        for (a, b) in pairs {
+            table.pairs.insert((a, b));
+            table.pairs.insert((b, a));
+
+            let type_a = table.unit_type(a);
+            let type_b = table.unit_type(b);
+            let (keep, merge) = if type_a <= type_b {
+                (type_a, type_b)
+            } else {
+                (type_b, type_a)
+            };
+            for t in table.types.values_mut().filter(|t| **t == merge) {
+                *t = keep;
+            }
+            table.types.insert(a, keep);
+            table.types.insert(b, keep);
+        }
+        table
+    }
+}
+
+impl ReactionRule for PairTable {
+    fn reacts(&self, a: char, b: char) -> bool {
+        self.pairs.contains(&(a, b))
+    }
+
+    fn unit_type(&self, unit: char) -> char {
+        *self.types.get(&unit).unwrap_or(&unit)
+    }
+}
+
+impl FromStr for PairTable {
+    type Err = String;
+
+    // One pair per line, as two units separated by whitespace: "a A". Empty lines
+    // and lines starting with '#' are skipped.
+    fn from_str(s: &str) -> Result<PairTable, String> {
+        let pairs = s
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| {
+                let units: Vec<char> = l.split_whitespace().flat_map(str::chars).collect();
+                match units[..] {
+                    [a, b] => Ok((a, b)),
+                    _ => Err(format!("Expected a pair of units, got {:?}", l)),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(PairTable::new(pairs))
+    }
+}
+
+// Reduces a polymer in a single pass. The units that survived so far sit on a
+// stack; a new unit either annihilates the top of the stack or is pushed onto it.
+// Since every unit is pushed and popped at most once, this is linear, and units
+// can be fed in one at a time as they are read.
+#[derive(Debug, Default, Clone)]
+pub struct Reactor<R> {
+    rule: R,
+    stack: Vec<char>,
+    reactions: Vec<(char, char)>,
+}
+
+impl<R: ReactionRule> Reactor<R> {
+    pub fn new(rule: R) -> Reactor<R> {
+        Reactor {
+            rule,
+            stack: Vec::new(),
+            reactions: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, unit: char) {
+        match self.stack.last() {
+            Some(&top) if self.rule.reacts(top, unit) => {
+                self.stack.pop();
+                self.reactions.push((top, unit));
+            }
+            _ => self.stack.push(unit),
+        }
+    }
+
+    pub fn polymer(&self) -> &[char] {
+        &self.stack
+    }
+
+    pub fn into_polymer(self) -> Vec<char> {
+        self.stack
+    }
+
+    // Every pair of units that annihilated, in the order it happened
+    pub fn reactions(&self) -> &[(char, char)] {
+        &self.reactions
+    }
+
+    // The unit types that were removed by reactions, in order of their first
+    // reaction, with the number of units of each type removed
+    pub fn removed_types(&self) -> Vec<(char, usize)> {
+        let mut removed: Vec<(char, usize)> = Vec::new();
+        for &(a, b) in &self.reactions {
+            for t in [self.rule.unit_type(a), self.rule.unit_type(b)].iter() {
+                match removed.iter_mut().find(|(seen, _)| seen == t) {
+                    Some((_, count)) => *count += 1,
+                    None => removed.push((*t, 1)),
+                }
+            }
+        }
+        removed
+    }
+}
+
+impl<R: ReactionRule> Extend<char> for Reactor<R> {
+    fn extend<I: IntoIterator<Item = char>>(&mut self, units: I) {
+        for unit in units {
+            self.push(unit);
+        }
+    }
+}
+
+pub fn react<R: ReactionRule, I: IntoIterator<Item = char>>(rule: R, units: I) -> Vec<char> {
+    let mut reactor = Reactor::new(rule);
+    reactor.extend(units);
+    reactor.into_polymer()
+}
+
+// Tries removing every unit type present in the polymer and returns the one giving
+// the shortest polymer, with that length. An empty polymer has no types to remove.
+//
+// With a confluent rule, any reaction in the full polymer also happens once a unit
+// type is removed, so the polymer may be passed already reduced. Otherwise the
+// order of reactions matters, and it has to be the original input.
+pub fn best_removal<R: ReactionRule + Sync>(rule: &R, polymer: &[char]) -> Option<(char, usize)> {
+    let mut types: Vec<char> = polymer.iter().map(|&u| rule.unit_type(u)).collect();
+    types.sort_unstable();
+    types.dedup();
+
+    types
+        .into_par_iter()
+        .map(|t| {
+            let remaining = polymer.iter().cloned().filter(|&u| rule.unit_type(u) != t);
+            (t, react(rule, remaining).len())
+        })
+        .min_by_key(|&(t, len)| (len, t))
+}
+
+#[test]
+fn test() {
+    assert_eq!(react(AsciiCase, "aA".chars()), vec![]);
+    assert_eq!(react(AsciiCase, "abBA".chars()), vec![]);
+    assert_eq!(react(AsciiCase, "aabAAB".chars()).len(), 6);
+
+    let reduced = react(AsciiCase, "dabAcCaCBAcCcaDA".chars());
+    assert_eq!(reduced.iter().collect::<String>(), "dabCBAcaDA");
+    assert_eq!(best_removal(&AsciiCase, &reduced), Some(('c', 4)));
+
+    // A polymer that reacts away completely
+    let reduced = react(AsciiCase, "abBA".chars());
+    assert!(reduced.is_empty());
+    assert_eq!(best_removal(&AsciiCase, &reduced), None);
+}
+
+#[test]
+fn test_rules() {
+    let mut reactor = Reactor::new(AsciiCase);
+    reactor.extend("dabAcCaCBAcCcaDA".chars());
+    assert_eq!(reactor.reactions(), &[('c', 'C'), ('A', 'a'), ('c', 'C')]);
+    assert_eq!(reactor.removed_types(), vec![('c', 4), ('a', 2)]);
+
+    assert_eq!(react(AsciiCase, "äÄ".chars()).len(), 2);
+    assert_eq!(react(UnicodeCase, "xäÄΣσ".chars()), vec!['x']);
+
+    // Here a and b annihilate each other, as do b and c, but a and c don't
+    let table: PairTable = "# chain\na b\n\nb c".parse().unwrap();
+    assert_eq!(react(&table, "abcbacc".chars()), vec!['a', 'c', 'c']);
+    assert_eq!(table.unit_type('c'), 'a');
+    assert_eq!(table.unit_type('x'), 'x');
+    assert!("a b c".parse::<PairTable>().is_err());
+
+    // Removing t (of type T) lets the whole input react away, but reducing it first leaves a
+    // and d behind, which don't react with each other
+    let table: PairTable = "a b\nb c\nc d\nt T".parse().unwrap();
+    assert!(!table.confluent());
+    let input: Vec<char> = "atbcd".chars().collect();
+    let reduced = react(&table, input.iter().cloned());
+    assert_eq!(reduced, vec!['a', 't', 'd']);
+    assert_eq!(best_removal(&table, &input), Some(('T', 0)));
+}
@@ -1,48 +1,68 @@
+use puzzle05::{AsciiCase, PairTable, ReactionRule, Reactor};
+use std::env;
+use std::fs;
 use std::io; // provides io's stdin()
 use std::io::BufRead;
 
 fn main() {
+    // Optionally, the first argument names a file with reacting pairs of units
+    match env::args().nth(1) {
+        Some(path) => {
+            let rules = fs::read_to_string(&path).expect("Cannot read rules file");
+            solve(rules.parse::<PairTable>().expect("Invalid rules file"))
+        }
+        None => solve(AsciiCase),
+    }
+}
+
+fn solve<R: ReactionRule + Sync>(rule: R) {
+    // Feed the polymer straight from stdin into the reactor. Only when the order of
+    // reactions matters is the unreacted input needed later on.
     let stdin = io::stdin();
-    let elements: Vec<_> = stdin
-        .lock()
-        .lines()
-        .filter_map(Result::ok)
-        .next()
-        .expect("No input")
-        .bytes()
-        .collect();
+    let mut reactor = Reactor::new(&rule);
+    let mut input = Vec::new();
+    let keep_input = !rule.confluent();
+    reactor.extend(
+        utf8_chars(stdin.lock())
+            .filter(|c| !c.is_whitespace())
+            .inspect(|&c| {
+                if keep_input {
+                    input.push(c)
+                }
+            }),
+    );
+
+    println!("{:?}", reactor.polymer().len());
+    let removed_types = reactor.removed_types();
 
-    println!("{:?}", react(elements.clone()));
+    let reduced = reactor.into_polymer();
+    // A polymer that reacts away completely has no unit types left to remove, and
+    // stays empty
+    let polymer = if keep_input { &input } else { &reduced };
     println!(
         "{:?}",
-        (0..26)
-            .map(|i| react(remove_elem(&elements, 'A' as u8 + i)))
-            .min()
-            .unwrap()
+        puzzle05::best_removal(&rule, polymer).map_or(0, |best| best.1)
     );
-}
-
-const CAPITAL: u8 = ('a' as u8) - ('A' as u8);
-
-fn react(mut elements: Vec<u8>) -> usize {
-    let mut i = 0;
-    while i + 1 < elements.len() {
-        if elements[i] == elements[i + 1] + CAPITAL || elements[i] + CAPITAL == elements[i + 1] {
-            elements.drain(i..(i + 2));
-            if i > 0 {
-                i -= 1;
-            }
-        } else {
-            i += 1;
-        }
-    }
 
-    elements.len()
+    // Which unit types reacted away in part 1, in order of their first reaction
+    println!("{:?}", removed_types);
 }
 
-fn remove_elem(v: &Vec<u8>, elem: u8) -> Vec<u8> {
-    v.iter()
-        .filter(|&&n| n != elem && n != elem + CAPITAL)
-        .map(|&n| n)
-        .collect()
+// Decodes a byte stream into chars one at a time
+fn utf8_chars<T: BufRead>(input: T) -> impl Iterator<Item = char> {
+    let mut bytes = input.bytes().map(|b| b.expect("Read error"));
+    std::iter::from_fn(move || {
+        let first = bytes.next()?;
+        let len = match first.leading_ones() {
+            0 => 1,
+            n @ 2..=4 => n as usize,
+            _ => panic!("Input is not valid UTF-8"),
+        };
+        let mut buf = [first, 0, 0, 0];
+        for b in buf.iter_mut().take(len).skip(1) {
+            *b = bytes.next().expect("Input ends halfway a char");
+        }
+        let s = std::str::from_utf8(&buf[..len]).expect("Input is not valid UTF-8");
+        s.chars().next()
+    })
 }
@@ -0,0 +1,67 @@
+use crate::voronoi::{Owner, Voronoi};
+use std::collections::HashSet;
+
+const TIE: [u8; 3] = [128, 128, 128];
+const SITE: [u8; 3] = [0, 0, 0];
+
+// Renders the diagram as a binary PPM image, one pixel per cell. Every region gets
+// its own colour and ties are grey. The largest finite region is drawn at full
+// brightness while the others are dimmed, and the sites themselves are black.
+pub fn ppm(voronoi: &Voronoi, sites: &[(i64, i64)]) -> Vec<u8> {
+    let mut image = format!("P6\n{} {}\n255\n", voronoi.width(), voronoi.height()).into_bytes();
+    for pixel in pixels(voronoi, sites).iter().flatten() {
+        image.extend_from_slice(pixel);
+    }
+    image
+}
+
+// The colours of the image, row by row
+fn pixels(voronoi: &Voronoi, sites: &[(i64, i64)]) -> Vec<Vec<[u8; 3]>> {
+    let winner = voronoi.largest_finite().map(|(i, _)| i);
+    let sites: HashSet<(i64, i64)> = sites.iter().cloned().collect();
+
+    (voronoi.min.1..=voronoi.max.1)
+        .map(|y| {
+            (voronoi.min.0..=voronoi.max.0)
+                .map(|x| match voronoi.owner((x, y)).unwrap() {
+                    _ if sites.contains(&(x, y)) => SITE,
+                    Owner::Tie => TIE,
+                    Owner::Site(i) if winner.is_some() && winner != Some(i) => {
+                        let [r, g, b] = colour(i);
+                        [r / 2, g / 2, b / 2]
+                    }
+                    Owner::Site(i) => colour(i),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// Spreads the hues of consecutive sites around the colour wheel by the golden
+// angle, so that neighbouring indices get clearly different colours.
+fn colour(site: usize) -> [u8; 3] {
+    let hue = (site as f64 * 137.507_764) % 360.0;
+    let sector = hue / 60.0;
+    let rising = (255.0 * (1.0 - (sector % 2.0 - 1.0).abs())) as u8;
+    match sector as u32 {
+        0 => [255, rising, 0],
+        1 => [rising, 255, 0],
+        2 => [0, 255, rising],
+        3 => [0, rising, 255],
+        4 => [rising, 0, 255],
+        _ => [255, 0, rising],
+    }
+}
+
+#[test]
+fn test() {
+    // The example's largest finite region belongs to site 4, at (5, 5)
+    let sites = crate::voronoi::parse(include_str!("../../input2"));
+    let voronoi = Voronoi::new(&sites);
+    let image = pixels(&voronoi, &sites);
+    let pixel = |x: i64, y: i64| image[(y - voronoi.min.1) as usize][(x - voronoi.min.0) as usize];
+    assert_eq!(pixel(5, 5), SITE);
+    assert_eq!(pixel(5, 2), colour(4));
+    let [r, g, b] = colour(3);
+    assert_eq!(pixel(4, 4), [r / 2, g / 2, b / 2]);
+}
@@ -0,0 +1,72 @@
+use crate::voronoi::Point;
+
+// The number of cells whose total Manhattan distance to all points is less than
+// `threshold`.
+//
+// The total distance splits into a sum over x and a sum over y, which we compute
+// separately for every column and row. Outside the bounding box, every step away
+// from it adds n (the number of points) to the sum, so no cell further than
+// threshold / n from the box can be in the region; that margin bounds the search.
+pub fn safe_region_size(points: &[Point], threshold: i64) -> usize {
+    if points.is_empty() {
+        return 0;
+    }
+    let xs: Vec<i64> = points.iter().map(|p| p.0).collect();
+    let ys: Vec<i64> = points.iter().map(|p| p.1).collect();
+    let column_sums = axis_sums(xs, threshold);
+    let mut row_sums = axis_sums(ys, threshold);
+    row_sums.sort_unstable();
+
+    // For every column, count the rows that keep the total under the threshold
+    column_sums
+        .iter()
+        .map(|&sx| row_sums.partition_point(|&sy| sx + sy < threshold))
+        .sum()
+}
+
+// The sum of distances to all coordinates, for every position that could still be
+// under the threshold.
+fn axis_sums(mut coords: Vec<i64>, threshold: i64) -> Vec<i64> {
+    coords.sort_unstable();
+    let n = coords.len() as i64;
+    let margin = threshold.max(0) / n + 1;
+    let (start, end) = (coords[0] - margin, coords[coords.len() - 1] + margin);
+
+    // Moving one step right adds 1 for each coordinate at or left of the current
+    // position, and subtracts 1 for each one to the right of it
+    let mut sum: i64 = coords.iter().map(|&c| c - start).sum();
+    let mut at_or_left = 0;
+    let mut sums = Vec::with_capacity((end - start + 1) as usize);
+    for pos in start..=end {
+        while at_or_left < coords.len() && coords[at_or_left] <= pos {
+            at_or_left += 1;
+        }
+        sums.push(sum);
+        sum += at_or_left as i64 - (n - at_or_left as i64);
+    }
+    sums
+}
+
+#[test]
+fn test() {
+    let points = crate::voronoi::parse(include_str!("../../input2"));
+    assert_eq!(safe_region_size(&points, 32), 16);
+    assert_eq!(safe_region_size(&points, 0), 0);
+    assert_eq!(safe_region_size(&[], 32), 0);
+
+    // Compare with a brute force scan over a generous area, for thresholds that
+    // make the region grow well beyond the bounding box
+    for &threshold in &[1, 20, 33, 100, 1000] {
+        let brute_force = (-300..300)
+            .flat_map(|y| (-300..300).map(move |x| (x, y)))
+            .filter(|&(x, y)| {
+                points
+                    .iter()
+                    .map(|p| (p.0 - x).abs() + (p.1 - y).abs())
+                    .sum::<i64>()
+                    < threshold
+            })
+            .count();
+        assert_eq!(safe_region_size(&points, threshold), brute_force);
+    }
+}
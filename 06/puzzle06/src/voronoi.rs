@@ -0,0 +1,186 @@
+use std::collections::VecDeque;
+
+pub type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Owner {
+    Site(usize),
+    // Two or more sites are equally close
+    Tie,
+}
+
+impl Owner {
+    fn merge(self, other: Owner) -> Owner {
+        if self == other {
+            self
+        } else {
+            Owner::Tie
+        }
+    }
+}
+
+// A Manhattan Voronoi diagram of the bounding box of the sites. Every cell in the
+// box is labelled with its closest site, or as a tie.
+#[derive(Debug, Clone)]
+pub struct Voronoi {
+    pub min: Point,
+    pub max: Point,
+    sites: usize,
+    cells: Vec<Owner>,
+}
+
+impl Voronoi {
+    // Labels the cells with a breadth-first search from all sites at once. Inside the
+    // box, BFS distance equals Manhattan distance, and the closest sites of a cell
+    // are exactly the closest sites of its neighbours one step nearer. Merging the
+    // owners of those neighbours therefore labels every cell, ties included.
+    pub fn new(sites: &[Point]) -> Voronoi {
+        let min = (
+            sites.iter().map(|p| p.0).min().unwrap_or(0),
+            sites.iter().map(|p| p.1).min().unwrap_or(0),
+        );
+        let max = (
+            sites.iter().map(|p| p.0).max().unwrap_or(-1),
+            sites.iter().map(|p| p.1).max().unwrap_or(-1),
+        );
+        let mut voronoi = Voronoi {
+            min,
+            max,
+            sites: sites.len(),
+            cells: Vec::new(),
+        };
+
+        let mut labels: Vec<Option<(usize, Owner)>> =
+            vec![None; voronoi.width() * voronoi.height()];
+        let mut queue = VecDeque::new();
+        for (i, &p) in sites.iter().enumerate() {
+            let idx = voronoi.index(p).unwrap();
+            labels[idx] = match labels[idx] {
+                None => {
+                    queue.push_back(p);
+                    Some((0, Owner::Site(i)))
+                }
+                Some((_, owner)) => Some((0, owner.merge(Owner::Site(i)))),
+            };
+        }
+
+        while let Some(p) = queue.pop_front() {
+            let (dist, owner) = labels[voronoi.index(p).unwrap()].unwrap();
+            for &n in &[
+                (p.0 - 1, p.1),
+                (p.0 + 1, p.1),
+                (p.0, p.1 - 1),
+                (p.0, p.1 + 1),
+            ] {
+                if let Some(idx) = voronoi.index(n) {
+                    labels[idx] = match labels[idx] {
+                        None => {
+                            queue.push_back(n);
+                            Some((dist + 1, owner))
+                        }
+                        Some((d, other)) if d == dist + 1 => Some((d, other.merge(owner))),
+                        label => label,
+                    };
+                }
+            }
+        }
+
+        voronoi.cells = labels.into_iter().map(|l| l.unwrap().1).collect();
+        voronoi
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1) as usize
+    }
+
+    fn index(&self, p: Point) -> Option<usize> {
+        if p.0 < self.min.0 || p.0 > self.max.0 || p.1 < self.min.1 || p.1 > self.max.1 {
+            return None;
+        }
+        Some((p.1 - self.min.1) as usize * self.width() + (p.0 - self.min.0) as usize)
+    }
+
+    // The owner of a cell, or None outside the bounding box
+    pub fn owner(&self, p: Point) -> Option<Owner> {
+        self.index(p).map(|idx| self.cells[idx])
+    }
+
+    // A region is infinite exactly when it reaches the edge of the bounding box:
+    // beyond the edge, moving further outwards brings you closer to no site at all.
+    pub fn infinite(&self) -> Vec<bool> {
+        let mut infinite = vec![false; self.sites];
+        let (w, h) = (self.width(), self.height());
+        let edge = (0..w)
+            .flat_map(|x| vec![x, (h - 1) * w + x])
+            .chain((0..h).flat_map(|y| vec![y * w, y * w + w - 1]));
+        for idx in edge {
+            if let Owner::Site(i) = self.cells[idx] {
+                infinite[i] = true;
+            }
+        }
+        infinite
+    }
+
+    // The area of every site's region, or None for infinite regions
+    pub fn areas(&self) -> Vec<Option<usize>> {
+        let mut areas = vec![0; self.sites];
+        for &cell in &self.cells {
+            if let Owner::Site(i) = cell {
+                areas[i] += 1;
+            }
+        }
+        areas
+            .into_iter()
+            .zip(self.infinite())
+            .map(|(area, infinite)| if infinite { None } else { Some(area) })
+            .collect()
+    }
+
+    // The site with the largest finite region, and that area
+    pub fn largest_finite(&self) -> Option<(usize, usize)> {
+        self.areas()
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, area)| Some((i, area?)))
+            .rev()
+            .max_by_key(|&(_, area)| area)
+    }
+}
+
+#[cfg(test)]
+pub fn parse(input: &str) -> Vec<Point> {
+    input
+        .lines()
+        .map(|l| {
+            let (x, y) = l.split_once(", ").unwrap();
+            (x.parse().unwrap(), y.parse().unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn test() {
+    let voronoi = Voronoi::new(&parse(include_str!("../../input2")));
+    assert_eq!(
+        voronoi.areas(),
+        vec![None, None, None, Some(9), Some(17), None]
+    );
+    assert_eq!(voronoi.largest_finite(), Some((4, 17)));
+    assert_eq!(voronoi.owner((5, 2)), Some(Owner::Site(4)));
+    assert_eq!(voronoi.owner((5, 1)), Some(Owner::Tie));
+    assert_eq!(voronoi.owner((0, 0)), None);
+
+    // (5, 3) only reaches the bottom edge of the box, which the old check missed
+    let voronoi = Voronoi::new(&[(0, 0), (10, 0), (5, 3)]);
+    assert_eq!(voronoi.infinite(), vec![true, true, true]);
+    assert_eq!(voronoi.largest_finite(), None);
+
+    // Duplicate sites tie with each other
+    let voronoi = Voronoi::new(&[(0, 0), (2, 2), (2, 2)]);
+    assert_eq!(voronoi.owner((2, 2)), Some(Owner::Tie));
+    assert_eq!(voronoi.owner((0, 1)), Some(Owner::Site(0)));
+}
@@ -0,0 +1,175 @@
+use crate::schedule::schedule;
+use crate::task_graph::{GraphError, TaskGraph};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub duration: usize,
+    pub earliest_start: usize,
+    pub latest_start: usize,
+}
+
+impl Timing {
+    pub fn earliest_finish(&self) -> usize {
+        self.earliest_start + self.duration
+    }
+
+    // How long the task can be delayed without delaying the whole project
+    pub fn slack(&self) -> usize {
+        self.latest_start - self.earliest_start
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    // Indexed by task
+    pub timings: Vec<Timing>,
+    // The time it takes with unlimited workers
+    pub duration: usize,
+    // A chain of tasks without slack, from a task without prerequisites to one
+    // nothing depends on
+    pub critical_path: Vec<usize>,
+    // The fewest workers for which the puzzle's scheduling finishes in `duration`
+    pub min_workers: usize,
+}
+
+// The critical path method: a forward pass over the tasks in dependency order gives
+// the earliest start times, a backward pass the latest start times that don't delay
+// the project.
+pub fn analyse<F>(graph: &TaskGraph, duration: F) -> Result<Analysis, GraphError>
+where
+    F: Fn(&str) -> usize,
+{
+    let order = graph.topological_order()?;
+    let durations: Vec<usize> = (0..graph.len()).map(|t| duration(graph.name(t))).collect();
+
+    let mut earliest_start = vec![0; graph.len()];
+    for &t in &order {
+        earliest_start[t] = graph
+            .predecessors(t)
+            .iter()
+            .map(|&p| earliest_start[p] + durations[p])
+            .max()
+            .unwrap_or(0);
+    }
+    let total = (0..graph.len())
+        .map(|t| earliest_start[t] + durations[t])
+        .max()
+        .unwrap_or(0);
+
+    let mut latest_start = vec![0; graph.len()];
+    for &t in order.iter().rev() {
+        let latest_finish = graph
+            .successors(t)
+            .iter()
+            .map(|&s| latest_start[s])
+            .min()
+            .unwrap_or(total);
+        latest_start[t] = latest_finish - durations[t];
+    }
+
+    let timings: Vec<Timing> = (0..graph.len())
+        .map(|t| Timing {
+            duration: durations[t],
+            earliest_start: earliest_start[t],
+            latest_start: latest_start[t],
+        })
+        .collect();
+
+    // Follow tasks without slack that start right when the previous one finishes,
+    // picking alphabetically when there are several
+    let critical = |t: &usize| timings[*t].slack() == 0;
+    let mut critical_path = Vec::new();
+    let mut next = order
+        .iter()
+        .cloned()
+        .filter(|&t| graph.predecessors(t).is_empty())
+        .filter(critical)
+        .min_by_key(|&t| graph.name(t));
+    while let Some(t) = next {
+        critical_path.push(t);
+        next = graph
+            .successors(t)
+            .iter()
+            .cloned()
+            .filter(critical)
+            .filter(|&s| timings[s].earliest_start == timings[t].earliest_finish())
+            .min_by_key(|&s| graph.name(s));
+    }
+
+    // Finding the optimal schedule for a given number of workers is hard in general,
+    // so this uses the puzzle's greedy scheduling. It always succeeds with one worker
+    // per task, and a schedule needs at least one worker even without tasks.
+    let min_workers = (1..graph.len())
+        .find(|&w| schedule(graph, w, |name| duration(name)).map(|s| s.total_time) == Ok(total))
+        .unwrap_or_else(|| graph.len().max(1));
+
+    Ok(Analysis {
+        timings,
+        duration: total,
+        critical_path,
+        min_workers,
+    })
+}
+
+impl Analysis {
+    pub fn report(&self, graph: &TaskGraph) -> String {
+        let name_width = (0..graph.len())
+            .map(|t| graph.name(t).len())
+            .max()
+            .unwrap_or(1);
+        let mut out = format!(
+            "{:w$}  duration  earliest start  latest start  slack\n",
+            "task",
+            w = name_width.max(4)
+        );
+        for t in graph.topological_order().unwrap_or_default() {
+            let timing = &self.timings[t];
+            out += &format!(
+                "{:w$}  {:>8}  {:>14}  {:>12}  {:>5}\n",
+                graph.name(t),
+                timing.duration,
+                timing.earliest_start,
+                timing.latest_start,
+                timing.slack(),
+                w = name_width.max(4)
+            );
+        }
+        let path: Vec<&str> = self.critical_path.iter().map(|&t| graph.name(t)).collect();
+        out += &format!("Critical path: {}\n", path.join(" -> "));
+        out += &format!(
+            "Takes {} seconds with at least {} workers\n",
+            self.duration, self.min_workers
+        );
+        out
+    }
+}
+
+#[test]
+fn test() {
+    use crate::schedule::letter_duration;
+
+    let graph: TaskGraph = include_str!("../../input2").parse().unwrap();
+    let analysis = analyse(&graph, |name| letter_duration(name, 0).unwrap()).unwrap();
+    assert_eq!(analysis.duration, 14);
+    let path: Vec<&str> = analysis
+        .critical_path
+        .iter()
+        .map(|&t| graph.name(t))
+        .collect();
+    assert_eq!(path, vec!["C", "F", "E"]);
+
+    let find = |name| (0..graph.len()).find(|&t| graph.name(t) == name).unwrap();
+    let f = find("F");
+    assert_eq!(analysis.timings[f].earliest_start, 3);
+    assert_eq!(analysis.timings[f].latest_start, 3);
+    let b = find("B");
+    assert_eq!(analysis.timings[b].earliest_start, 4);
+    assert_eq!(analysis.timings[b].slack(), 3);
+
+    // Two workers take 15 seconds, as in the puzzle, one more than the critical
+    // path, so it takes a third worker to finish in 14
+    assert_eq!(analysis.min_workers, 3);
+
+    let analysis = analyse(&TaskGraph::new(), |_| 1).unwrap();
+    assert_eq!((analysis.duration, analysis.min_workers), (0, 1));
+}
@@ -1,126 +1,93 @@
-extern crate itertools;
 extern crate regex;
 
 #[macro_use]
 extern crate lazy_static;
 
-use itertools::Itertools;
-use regex::Regex;
-use std::cmp::max;
+mod critical_path;
+mod schedule;
+mod task_graph;
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
 use std::io;
-use std::io::BufRead;
-use std::io::Error;
+use std::io::Read;
+use task_graph::TaskGraph;
 
 fn main() {
-    let task_order: Vec<(char, char)> = io::stdin().lock().lines().map(|l| parse_line(l)).collect();
-
-    let highest = task_order.iter().fold('A', |m, (a, b)| max(m, max(*a, *b)));
-    let mut all: Vec<char> = CharRangeInclusive('A', highest).collect();
-
-    // Part 1. Do the tasks in order
-    let done: Vec<char> = TaskIterator(&all, Vec::new(), &task_order).collect();
-    println!("{:}", done.iter().collect::<String>());
-
-    // Part 2. Do the tasks in parallel, measure time
-    const WORKERS: usize = 5;
-    const EXTRA_TIME: usize = 60;
-
-    let mut workers: Vec<Task> = vec![Default::default(); WORKERS];
-    let mut done = Vec::new();
-    let mut time = 0;
-    let mut time_delta = 1;
-
-    loop {
-        // Step all jobs, and free workers who are done
-        for w in workers.iter_mut() {
-            if let Some(task) = w.current {
-                w.time_left -= time_delta;
-                if w.time_left == 0 {
-                    done.push(task);
-                    *w = Default::default();
-                }
-            }
-        }
-        // Give new jobs to idle workers
-        for w in workers.iter_mut().filter(|w| w.current.is_none()) {
-            if let Some(next_task) = available(&all, &done, &task_order) {
-                *w = Task {
-                    current: Some(next_task),
-                    time_left: (next_task as usize) - ('A' as usize) + EXTRA_TIME + 1,
-                };
-                // Remove the task from the pile of stuff to do
-                all = all.into_iter().filter(|&t| t != next_task).collect();
+    // Usage: puzzle07 [--workers N] [--extra SECONDS] [--durations FILE] [--table | --gantt]
+    //                 [--critical]
+    let mut workers = 5;
+    let mut extra_time = 60;
+    let mut durations_file = None;
+    let mut output = None;
+    let mut critical = false;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| panic!("{} needs a value", arg))
+        };
+        match arg.as_str() {
+            "--workers" => {
+                workers = value()
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .expect("Invalid number of workers")
             }
+            "--extra" => extra_time = value().parse().expect("Invalid extra time"),
+            "--durations" => durations_file = Some(value()),
+            "--table" | "--gantt" => output = Some(arg),
+            "--critical" => critical = true,
+            _ => panic!("Unknown argument {}", arg),
         }
-        // If everyone is still idle, we're done
-        if workers.iter().filter_map(|w| w.current).count() == 0 {
-            break;
-        }
-        // To be faster, step time by min of length of active jobs
-        time_delta = workers
-            .iter()
-            .filter_map(|w| w.current.and(Some(w.time_left)))
-            .min()
-            .unwrap();
-        time += time_delta;
-    }
-
-    println!("{:?}", time);
-}
-
-fn parse_line(l: Result<String, Error>) -> (char, char) {
-    lazy_static! {
-        static ref re: regex::Regex = Regex::new(r" ([A-Z]) ").unwrap();
     }
-    re.captures_iter(&l.expect("Read error"))
-        .take(2)
-        .map(|s| s.get(1).unwrap().as_str().chars().next().unwrap())
-        .next_tuple()
-        .expect("Line did not fit the format")
-}
-
-fn available(wait: &Vec<char>, done: &Vec<char>, task_order: &Vec<(char, char)>) -> Option<char> {
-    let unavailable_tasks: Vec<char> = task_order
-        .iter()
-        .filter(|(prerequisite, _task)| !done.contains(&prerequisite))
-        .map(|(_undone_prerequisite, undoable_task)| *undoable_task)
-        .collect();
 
-    wait.iter()
-        .filter(|task| !done.contains(task) && !unavailable_tasks.contains(task))
-        .map(|&ch| ch)
-        .min()
-}
-
-#[derive(Clone, Default)]
-struct Task {
-    current: Option<char>,
-    time_left: usize,
-}
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input).expect("Read error");
+    let graph: TaskGraph = input.parse().expect("Line did not fit the format");
 
-struct CharRangeInclusive(char, char);
-impl Iterator for CharRangeInclusive {
-    type Item = char;
+    // Part 1. Do the tasks in order
+    let done = graph
+        .topological_order()
+        .expect("The tasks depend on each other");
+    println!(
+        "{:}",
+        done.iter().map(|&t| graph.name(t)).collect::<String>()
+    );
 
-    fn next(&mut self) -> Option<char> {
-        if self.0 > self.1 {
-            return None;
+    // Part 2. Do the tasks in parallel, measure time
+    let durations: HashMap<String, usize> = match durations_file {
+        Some(path) => {
+            let file = fs::read_to_string(path).expect("Cannot read durations file");
+            schedule::parse_durations(&file).expect("Invalid durations file")
         }
-        let v = self.0;
-        self.0 = (v as u8 + 1) as char;
-        Some(v)
+        None => (0..graph.len())
+            .filter_map(|t| {
+                let name = graph.name(t);
+                Some((
+                    name.to_owned(),
+                    schedule::letter_duration(name, extra_time)?,
+                ))
+            })
+            .collect(),
+    };
+    if let Some(t) = (0..graph.len()).find(|&t| !durations.contains_key(graph.name(t))) {
+        panic!("No duration known for task {}", graph.name(t));
     }
-}
 
-struct TaskIterator<'a>(&'a Vec<char>, Vec<char>, &'a Vec<(char, char)>);
-impl Iterator for TaskIterator<'_> {
-    type Item = char;
-
-    fn next(&mut self) -> Option<char> {
-        if let Some(next) = available(self.0, &self.1, self.2) {
-            self.1.push(next);
-            return Some(next);
-        }
-        None
+    let schedule = schedule::schedule(&graph, workers, |name| durations[name])
+        .unwrap_or_else(|e| panic!("Cannot schedule the tasks: {:?}", e));
+    match output.as_deref() {
+        Some("--table") => print!("{}", schedule.table(&graph)),
+        Some("--gantt") => print!("{}", schedule.gantt(&graph, 100)),
+        _ => {}
+    }
+    if critical {
+        let analysis = critical_path::analyse(&graph, |name| durations[name])
+            .expect("The tasks depend on each other");
+        print!("{}", analysis.report(&graph));
     }
+    println!("{:?}", schedule.total_time);
 }
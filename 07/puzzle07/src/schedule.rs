@@ -0,0 +1,228 @@
+use crate::task_graph::{GraphError, TaskGraph};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Assignment {
+    pub worker: usize,
+    pub task: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ScheduleError {
+    NoWorkers,
+    Graph(GraphError),
+}
+
+impl From<GraphError> for ScheduleError {
+    fn from(e: GraphError) -> ScheduleError {
+        ScheduleError::Graph(e)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    pub workers: usize,
+    // In order of starting time, then worker
+    pub assignments: Vec<Assignment>,
+    pub total_time: usize,
+}
+
+// Simulates the workers doing all tasks. Whenever a worker is idle, it takes the
+// first available task (alphabetically); the lowest numbered idle worker goes first.
+pub fn schedule<F>(
+    graph: &TaskGraph,
+    workers: usize,
+    duration: F,
+) -> Result<Schedule, ScheduleError>
+where
+    F: Fn(&str) -> usize,
+{
+    if workers == 0 {
+        return Err(ScheduleError::NoWorkers);
+    }
+    let mut queue = graph.ready_queue();
+    let mut assignments = Vec::new();
+    // The assignment each worker is busy with, as an index into assignments
+    let mut busy: Vec<Option<usize>> = vec![None; workers];
+    let mut time = 0;
+
+    loop {
+        // Give new jobs to idle workers
+        for (worker, current) in busy.iter_mut().enumerate() {
+            if current.is_none() {
+                if let Some(task) = queue.pop() {
+                    *current = Some(assignments.len());
+                    assignments.push(Assignment {
+                        worker,
+                        task,
+                        start: time,
+                        end: time + duration(graph.name(task)),
+                    });
+                }
+            }
+        }
+        // If everyone is still idle, we're done
+        let active = busy.iter().filter_map(|&a| a);
+        time = match active.map(|a| assignments[a].end).min() {
+            Some(t) => t,
+            None => break,
+        };
+        // Step time to the first job to finish, and free workers who are done
+        for current in busy.iter_mut() {
+            if let Some(a) = *current {
+                if assignments[a].end == time {
+                    queue.finish(assignments[a].task);
+                    *current = None;
+                }
+            }
+        }
+    }
+
+    queue.check_finished()?;
+    Ok(Schedule {
+        workers,
+        assignments,
+        total_time: time,
+    })
+}
+
+// The puzzle's durations: A takes 1 second, B takes 2, and so on, plus a fixed
+// extra time. Only single letter names have such a duration.
+pub fn letter_duration(name: &str, extra_time: usize) -> Option<usize> {
+    match name.as_bytes() {
+        [letter @ b'A'..=b'Z'] => Some((letter - b'A') as usize + 1 + extra_time),
+        _ => None,
+    }
+}
+
+// Reads durations from lines with a task name and its duration in seconds: "A 61"
+pub fn parse_durations(s: &str) -> Result<HashMap<String, usize>, String> {
+    s.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .map(|l| {
+            let mut words = l.split_whitespace();
+            match (words.next(), words.next().map(str::parse), words.next()) {
+                (Some(name), Some(Ok(duration)), None) => Ok((name.to_owned(), duration)),
+                _ => Err(format!("Expected a task name and a duration, got {:?}", l)),
+            }
+        })
+        .collect()
+}
+
+impl Schedule {
+    // What the worker is doing during the given second
+    fn task_at(&self, worker: usize, second: usize) -> Option<usize> {
+        self.assignments
+            .iter()
+            .find(|a| a.worker == worker && a.start <= second && second < a.end)
+            .map(|a| a.task)
+    }
+
+    // The second-by-second table from the puzzle description, showing what every
+    // worker is doing and which tasks are done
+    pub fn table(&self, graph: &TaskGraph) -> String {
+        let name_width = (0..graph.len())
+            .map(|t| graph.name(t).len())
+            .max()
+            .unwrap_or(1);
+        let separator = if name_width > 1 { "," } else { "" };
+
+        let mut finished: Vec<&Assignment> = self.assignments.iter().collect();
+        finished.sort_by_key(|a| (a.end, a.worker));
+
+        let column_width = |worker: usize| name_width.max(format!("Worker {}", worker + 1).len());
+        let mut header = String::from("Second");
+        for worker in 0..self.workers {
+            let title = format!("Worker {}", worker + 1);
+            header += &format!("  {:^width$}", title, width = column_width(worker));
+        }
+        let mut lines = vec![header + "  Done"];
+        for second in 0..=self.total_time {
+            let mut line = format!("{:>6}", second);
+            for worker in 0..self.workers {
+                let doing = self.task_at(worker, second).map_or(".", |t| graph.name(t));
+                line += &format!("  {:^width$}", doing, width = column_width(worker));
+            }
+            let done: Vec<&str> = finished
+                .iter()
+                .take_while(|a| a.end <= second)
+                .map(|a| graph.name(a.task))
+                .collect();
+            line += "  ";
+            line += &done.join(separator);
+            lines.push(line.trim_end().to_owned());
+        }
+        lines.join("\n") + "\n"
+    }
+
+    // One row per task showing when it was worked on, and by whom. Long schedules
+    // are scaled down to at most `columns` characters; a column is marked when the
+    // task was in progress at any time it covers.
+    pub fn gantt(&self, graph: &TaskGraph, columns: usize) -> String {
+        let columns = columns.max(1);
+        let seconds_per_column = self.total_time.div_ceil(columns).max(1);
+        let width = self.total_time.div_ceil(seconds_per_column);
+        let name_width = (0..graph.len())
+            .map(|t| graph.name(t).len())
+            .max()
+            .unwrap_or(1);
+
+        let mut out = format!(
+            "{} second(s) per column, {} seconds in total\n",
+            seconds_per_column, self.total_time
+        );
+        for a in &self.assignments {
+            let bar: String = (0..width)
+                .map(|c| {
+                    let (from, to) = (c * seconds_per_column, (c + 1) * seconds_per_column);
+                    if a.start < to && from < a.end {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            out += &format!(
+                "{:w$}  worker {:<3} |{}| {}-{}\n",
+                graph.name(a.task),
+                a.worker + 1,
+                bar,
+                a.start,
+                a.end,
+                w = name_width
+            );
+        }
+        out
+    }
+}
+
+#[test]
+fn test() {
+    let graph: TaskGraph = include_str!("../../input2").parse().unwrap();
+    let example = schedule(&graph, 2, |name| letter_duration(name, 0).unwrap()).unwrap();
+    assert_eq!(example.total_time, 15);
+
+    let table = example.table(&graph);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "Second  Worker 1  Worker 2  Done");
+    assert_eq!(lines[1], "     0     C         .");
+    assert_eq!(lines[4], "     3     A         F      C");
+    assert_eq!(lines[16], "    15     .         .      CABFDE");
+
+    let gantt = example.gantt(&graph, 100);
+    assert!(gantt.contains("F  worker 2   |...######......| 3-9\n"));
+
+    let durations = parse_durations("C 1\nA 2\nB 3\nD 4\nE 5\nF 6\n").unwrap();
+    let one_worker = schedule(&graph, 1, |name| durations[name]).unwrap();
+    assert_eq!(one_worker.total_time, 21);
+    assert_eq!(
+        schedule(&graph, 0, |name| durations[name]).unwrap_err(),
+        ScheduleError::NoWorkers
+    );
+    assert!(parse_durations("C one").is_err());
+    assert_eq!(letter_duration("Z", 60), Some(86));
+    assert_eq!(letter_duration("build", 60), None);
+}
@@ -0,0 +1,234 @@
+use regex::Regex;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GraphError {
+    Malformed(String),
+    // The tasks forming a cycle, each one a prerequisite of the next
+    Cycle(Vec<String>),
+    // Tasks that could have been started, but never were
+    NotStarted(Vec<String>),
+}
+
+// A dependency graph between tasks with arbitrary names. Tasks are numbered in the
+// order they were first mentioned; edges are stored as adjacency lists both ways.
+#[derive(Debug, Default, Clone)]
+pub struct TaskGraph {
+    names: Vec<String>,
+    index: HashMap<String, usize>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl TaskGraph {
+    pub fn new() -> TaskGraph {
+        TaskGraph::default()
+    }
+
+    pub fn add_task(&mut self, name: &str) -> usize {
+        if let Some(&i) = self.index.get(name) {
+            return i;
+        }
+        let i = self.names.len();
+        self.names.push(name.to_owned());
+        self.index.insert(name.to_owned(), i);
+        self.successors.push(Vec::new());
+        self.predecessors.push(Vec::new());
+        i
+    }
+
+    // `before` must be finished before `after` can begin
+    pub fn add_dependency(&mut self, before: &str, after: &str) {
+        let (b, a) = (self.add_task(before), self.add_task(after));
+        if !self.successors[b].contains(&a) {
+            self.successors[b].push(a);
+            self.predecessors[a].push(b);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn name(&self, task: usize) -> &str {
+        &self.names[task]
+    }
+
+    pub fn successors(&self, task: usize) -> &[usize] {
+        &self.successors[task]
+    }
+
+    pub fn predecessors(&self, task: usize) -> &[usize] {
+        &self.predecessors[task]
+    }
+
+    // Keeps track of which tasks are ready to start as tasks are finished. Among the
+    // ready tasks, the alphabetically first name comes out first.
+    pub fn ready_queue(&self) -> ReadyQueue<'_> {
+        let waiting_on: Vec<usize> = self.predecessors.iter().map(Vec::len).collect();
+        let ready = (0..self.len())
+            .filter(|&t| waiting_on[t] == 0)
+            .map(|t| Reverse((self.name(t), t)))
+            .collect();
+        ReadyQueue {
+            graph: self,
+            waiting_on,
+            ready,
+            finished: vec![false; self.len()],
+        }
+    }
+
+    // All tasks in an order that respects the dependencies, choosing alphabetically
+    // whenever several tasks are available
+    pub fn topological_order(&self) -> Result<Vec<usize>, GraphError> {
+        let mut queue = self.ready_queue();
+        let mut order = Vec::with_capacity(self.len());
+        while let Some(task) = queue.pop() {
+            queue.finish(task);
+            order.push(task);
+        }
+        queue.check_finished()?;
+        Ok(order)
+    }
+
+    // Finds a cycle among the given tasks, each of which must have at least one
+    // prerequisite among them. Walking back over prerequisites must then revisit a
+    // task eventually, and the walk from there on is a cycle. It is returned starting
+    // from the task that was mentioned first.
+    fn find_cycle(&self, unfinished: &[bool]) -> Vec<String> {
+        let mut visited_at = HashMap::new();
+        let mut walk = Vec::new();
+        let mut task = unfinished.iter().position(|&u| u).unwrap();
+        while !visited_at.contains_key(&task) {
+            visited_at.insert(task, walk.len());
+            walk.push(task);
+            task = *self.predecessors[task]
+                .iter()
+                .find(|&&p| unfinished[p])
+                .unwrap();
+        }
+        let mut cycle = walk.split_off(visited_at[&task]);
+        cycle.reverse();
+        let first = cycle.iter().enumerate().min_by_key(|&(_, t)| t).unwrap().0;
+        cycle.rotate_left(first);
+        cycle.iter().map(|&t| self.names[t].clone()).collect()
+    }
+}
+
+pub struct ReadyQueue<'a> {
+    graph: &'a TaskGraph,
+    waiting_on: Vec<usize>,
+    ready: BinaryHeap<Reverse<(&'a str, usize)>>,
+    finished: Vec<bool>,
+}
+
+impl ReadyQueue<'_> {
+    // Takes the next task that can be started
+    pub fn pop(&mut self) -> Option<usize> {
+        self.ready.pop().map(|Reverse((_, t))| t)
+    }
+
+    // Marks a task taken from this queue as finished, releasing its successors
+    pub fn finish(&mut self, task: usize) {
+        self.finished[task] = true;
+        for &s in self.graph.successors(task) {
+            self.waiting_on[s] -= 1;
+            if self.waiting_on[s] == 0 {
+                self.ready.push(Reverse((self.graph.name(s), s)));
+            }
+        }
+    }
+
+    // Once nothing is ready or in progress, every task should be finished. If not,
+    // either some tasks that were ready never got done, or the remaining tasks all
+    // wait on each other.
+    pub fn check_finished(&self) -> Result<(), GraphError> {
+        let unfinished: Vec<bool> = self.finished.iter().map(|&f| !f).collect();
+        if !unfinished.contains(&true) {
+            return Ok(());
+        }
+        let not_started: Vec<String> = (0..self.graph.len())
+            .filter(|&t| unfinished[t] && self.waiting_on[t] == 0)
+            .map(|t| self.graph.name(t).to_owned())
+            .collect();
+        if !not_started.is_empty() {
+            return Err(GraphError::NotStarted(not_started));
+        }
+        Err(GraphError::Cycle(self.graph.find_cycle(&unfinished)))
+    }
+}
+
+impl FromStr for TaskGraph {
+    type Err = GraphError;
+
+    // One dependency per line: "Step C must be finished before step A can begin."
+    fn from_str(s: &str) -> Result<TaskGraph, GraphError> {
+        lazy_static! {
+            static ref re: Regex =
+                Regex::new(r"^Step (\S+) must be finished before step (\S+) can begin\.$").unwrap();
+        }
+        let mut graph = TaskGraph::new();
+        for line in s.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let caps = re
+                .captures(line)
+                .ok_or_else(|| GraphError::Malformed(line.to_owned()))?;
+            graph.add_dependency(&caps[1], &caps[2]);
+        }
+        Ok(graph)
+    }
+}
+
+#[test]
+fn test() {
+    let graph: TaskGraph = include_str!("../../input2").parse().unwrap();
+    let order: Vec<&str> = graph
+        .topological_order()
+        .unwrap()
+        .into_iter()
+        .map(|t| graph.name(t))
+        .collect();
+    assert_eq!(order.concat(), "CABDFE");
+
+    let graph: TaskGraph = "Step build must be finished before step test can begin.
+        Step fetch must be finished before step build can begin.
+        Step fetch must be finished before step docs can begin."
+        .parse()
+        .unwrap();
+    let order: Vec<&str> = graph
+        .topological_order()
+        .unwrap()
+        .into_iter()
+        .map(|t| graph.name(t))
+        .collect();
+    assert_eq!(order, vec!["fetch", "build", "docs", "test"]);
+
+    let graph: TaskGraph = "Step A must be finished before step B can begin.
+        Step B must be finished before step C can begin.
+        Step C must be finished before step D can begin.
+        Step D must be finished before step B can begin."
+        .parse()
+        .unwrap();
+    assert_eq!(
+        graph.topological_order(),
+        Err(GraphError::Cycle(vec![
+            "B".to_owned(),
+            "C".to_owned(),
+            "D".to_owned()
+        ]))
+    );
+    assert!("Step A must be done before step B."
+        .parse::<TaskGraph>()
+        .is_err());
+
+    // Leaving a ready task in the queue isn't the same as a cycle
+    let graph: TaskGraph = include_str!("../../input2").parse().unwrap();
+    let mut queue = graph.ready_queue();
+    let c = queue.pop().unwrap();
+    queue.finish(c);
+    assert_eq!(
+        queue.check_finished(),
+        Err(GraphError::NotStarted(vec!["A".to_owned(), "F".to_owned()]))
+    );
+}
@@ -0,0 +1,274 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Node {
+    // Indices into the tree's node list
+    pub children: Vec<usize>,
+    pub metadata: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TreeError {
+    // The numbers ran out at this position while reading the given node
+    UnexpectedEnd { position: usize, node: usize },
+    // The tree was complete, but more numbers followed from this position on
+    TrailingNumbers { position: usize, count: usize },
+}
+
+// All nodes live in one Vec, in the order their headers appear in the input. The
+// root is node 0, and every child comes after its parent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tree {
+    pub nodes: Vec<Node>,
+}
+
+impl Tree {
+    // Parses the header/metadata format without recursion, so deep trees can't
+    // overflow the stack. Every number must belong to the tree; positions in errors
+    // count from 0.
+    pub fn parse(numbers: &[usize]) -> Result<Tree, TreeError> {
+        let mut position = 0;
+        let mut tree = Tree::default();
+        // For every node whose children are still being read: its index, how many
+        // children are left to read, and how much metadata follows them
+        let mut stack: Vec<(usize, usize, usize)> = Vec::new();
+
+        let header = take(numbers, &mut position, 2, 0)?;
+        tree.nodes.push(Node::default());
+        stack.push((0, header[0], header[1]));
+        while let Some(top) = stack.last_mut() {
+            let (node, children_left, num_metadata) = *top;
+            if children_left > 0 {
+                top.1 -= 1;
+                let child = tree.nodes.len();
+                let header = take(numbers, &mut position, 2, child)?;
+                tree.nodes.push(Node::default());
+                tree.nodes[node].children.push(child);
+                stack.push((child, header[0], header[1]));
+            } else {
+                let metadata = take(numbers, &mut position, num_metadata, node)?;
+                tree.nodes[node].metadata = metadata.to_vec();
+                stack.pop();
+            }
+        }
+
+        if position < numbers.len() {
+            return Err(TreeError::TrailingNumbers {
+                position,
+                count: numbers.len() - position,
+            });
+        }
+        Ok(tree)
+    }
+
+    // The inverse of parse: every node's header, then its children, then its metadata
+    pub fn serialize(&self) -> Vec<usize> {
+        let mut numbers = Vec::new();
+        // Nodes still to be written, and whether their children have been written yet
+        let mut stack = vec![(0, false)];
+        while let Some((node, children_done)) = stack.pop() {
+            let Some(n) = self.nodes.get(node) else {
+                break;
+            };
+            if children_done {
+                numbers.extend(&n.metadata);
+            } else {
+                numbers.push(n.children.len());
+                numbers.push(n.metadata.len());
+                stack.push((node, true));
+                stack.extend(n.children.iter().rev().map(|&c| (c, false)));
+            }
+        }
+        numbers
+    }
+
+    // The serialized tree in the puzzle's input format
+    pub fn to_input(&self) -> String {
+        let numbers: Vec<String> = self.serialize().iter().map(usize::to_string).collect();
+        numbers.join(" ")
+    }
+
+    // Part 1: the sum of all metadata
+    pub fn metadata_sum(&self) -> usize {
+        self.nodes.iter().flat_map(|n| n.metadata.iter()).sum()
+    }
+
+    // Part 2: the value of every node. A node without children is worth the sum of
+    // its metadata; otherwise each metadata entry i adds the value of child i
+    // (counting from 1), if it exists. Children come after their parents, so going
+    // through the nodes backwards computes every child before its parent.
+    pub fn values(&self) -> Vec<usize> {
+        let mut values = vec![0; self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate().rev() {
+            values[i] = if node.children.is_empty() {
+                node.metadata.iter().sum()
+            } else {
+                node.metadata
+                    .iter()
+                    .filter_map(|&m| node.children.get(m.checked_sub(1)?))
+                    .map(|&child| values[child])
+                    .sum()
+            };
+        }
+        values
+    }
+
+    pub fn root_value(&self) -> usize {
+        self.values().first().cloned().unwrap_or(0)
+    }
+}
+
+// Takes the next `count` numbers, which belong to the given node
+fn take<'a>(
+    numbers: &'a [usize],
+    position: &mut usize,
+    count: usize,
+    node: usize,
+) -> Result<&'a [usize], TreeError> {
+    let end = match position.checked_add(count) {
+        Some(end) if end <= numbers.len() => end,
+        _ => {
+            return Err(TreeError::UnexpectedEnd {
+                position: numbers.len(),
+                node,
+            })
+        }
+    };
+    let taken = &numbers[*position..end];
+    *position = end;
+    Ok(taken)
+}
+
+// Shows the tree with one node per line, children indented below their parent
+impl fmt::Display for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let values = self.values();
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if node >= self.nodes.len() {
+                break;
+            }
+            writeln!(
+                f,
+                "{:indent$}node {}: metadata {:?}, value {}",
+                "",
+                node,
+                self.nodes[node].metadata,
+                values[node],
+                indent = 2 * depth
+            )?;
+            for &child in self.nodes[node].children.iter().rev() {
+                stack.push((child, depth + 1));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    let tree = Tree::parse(&[2, 3, 0, 3, 10, 11, 12, 1, 1, 0, 1, 99, 2, 1, 1, 2]).unwrap();
+    assert_eq!(tree.nodes.len(), 4);
+    assert_eq!(tree.nodes[0].children, vec![1, 2]);
+    assert_eq!(tree.nodes[2].children, vec![3]);
+    assert_eq!(tree.metadata_sum(), 138);
+    assert_eq!(tree.values(), vec![66, 33, 0, 99]);
+    assert_eq!(
+        tree.to_string(),
+        "node 0: metadata [1, 1, 2], value 66
+  node 1: metadata [10, 11, 12], value 33
+  node 2: metadata [2], value 0
+    node 3: metadata [99], value 99
+"
+    );
+
+    // A chain far deeper than the call stack would allow with recursion
+    let depth = 1_000_000;
+    let mut numbers: Vec<usize> = (0..depth).flat_map(|_| vec![1, 1]).collect();
+    numbers.extend(&[0, 1, 7]);
+    numbers.extend((0..depth).map(|_| 1));
+    let tree = Tree::parse(&numbers).unwrap();
+    assert_eq!(tree.metadata_sum(), depth + 7);
+    assert_eq!(tree.root_value(), 7);
+
+    assert_eq!(
+        Tree::parse(&[1, 1, 0, 1, 5]),
+        Err(TreeError::UnexpectedEnd {
+            position: 5,
+            node: 0
+        })
+    );
+    assert_eq!(
+        Tree::parse(&[1, 1, 0]),
+        Err(TreeError::UnexpectedEnd {
+            position: 3,
+            node: 1
+        })
+    );
+    assert_eq!(
+        Tree::parse(&[]),
+        Err(TreeError::UnexpectedEnd {
+            position: 0,
+            node: 0
+        })
+    );
+    assert_eq!(
+        Tree::parse(&[0, usize::MAX]),
+        Err(TreeError::UnexpectedEnd {
+            position: 2,
+            node: 0
+        })
+    );
+    assert_eq!(
+        Tree::parse(&[0, 1, 5, 6, 7]),
+        Err(TreeError::TrailingNumbers {
+            position: 3,
+            count: 2
+        })
+    );
+}
+
+// A random tree with the given number of nodes. Nodes are added in the order they
+// are serialized, each one a child of a node on the path from the root to the
+// previous node.
+#[cfg(test)]
+fn random_tree(seed: u64, num_nodes: usize) -> Tree {
+    let mut state = seed | 1;
+    let mut random = |below: usize| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % below as u64) as usize
+    };
+
+    let mut tree = Tree::default();
+    tree.nodes.push(Node::default());
+    let mut path = vec![0];
+    for child in 1..num_nodes {
+        path.truncate(1 + random(path.len()));
+        tree.nodes[*path.last().unwrap()].children.push(child);
+        tree.nodes.push(Node::default());
+        path.push(child);
+    }
+    for node in tree.nodes.iter_mut() {
+        let count = random(4) + usize::from(node.children.is_empty());
+        node.metadata = (0..count)
+            .map(|_| random(node.children.len() + 3))
+            .collect();
+    }
+    tree
+}
+
+#[test]
+fn round_trip() {
+    let example = include_str!("../../input2").trim();
+    let numbers: Vec<usize> = example.split(' ').map(|n| n.parse().unwrap()).collect();
+    assert_eq!(Tree::parse(&numbers).unwrap().to_input(), example);
+
+    for seed in 0..200 {
+        let tree = random_tree(seed, 1 + seed as usize * 5);
+        let numbers = tree.serialize();
+        assert_eq!(Tree::parse(&numbers), Ok(tree));
+        assert!(Tree::parse(&numbers[..numbers.len() - 1]).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+// A circular doubly linked list of marbles. Every marble has a unique number, so
+// the links are stored in arrays indexed by marble: next[m] is the marble clockwise
+// of m, prev[m] the one counter-clockwise. Every operation is O(1) per step, no
+// matter how many marbles are in the circle.
+#[derive(Debug, Clone)]
+pub struct Circle {
+    next: Vec<usize>,
+    prev: Vec<usize>,
+    current: usize,
+}
+
+impl Circle {
+    // A circle holding only marble 0, with room for marbles up to `max_marble`
+    pub fn new(max_marble: usize) -> Circle {
+        Circle {
+            next: vec![0; max_marble + 1],
+            prev: vec![0; max_marble + 1],
+            current: 0,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    // Moves the current marble clockwise (or counter-clockwise, if negative)
+    pub fn rotate(&mut self, steps: isize) {
+        for _ in 0..steps {
+            self.current = self.next[self.current];
+        }
+        for _ in steps..0 {
+            self.current = self.prev[self.current];
+        }
+    }
+
+    // Places a marble clockwise of the current one, and makes it current
+    pub fn insert(&mut self, marble: usize) {
+        let (before, after) = (self.current, self.next[self.current]);
+        self.next[before] = marble;
+        self.prev[after] = marble;
+        self.next[marble] = after;
+        self.prev[marble] = before;
+        self.current = marble;
+    }
+
+    // Takes out the current marble; the one clockwise of it becomes current
+    pub fn remove(&mut self) -> usize {
+        let removed = self.current;
+        let (before, after) = (self.prev[removed], self.next[removed]);
+        self.next[before] = after;
+        self.prev[after] = before;
+        self.current = after;
+        removed
+    }
+
+    // All marbles clockwise, starting from the current one
+    #[cfg(test)]
+    pub fn marbles(&self) -> Vec<usize> {
+        let mut marbles = vec![self.current];
+        let mut marble = self.next[self.current];
+        while marble != self.current {
+            marbles.push(marble);
+            marble = self.next[marble];
+        }
+        marbles
+    }
+}
+
+#[test]
+fn test() {
+    let mut circle = Circle::new(10);
+    for marble in 1..=5 {
+        circle.rotate(1);
+        circle.insert(marble);
+    }
+    assert_eq!(circle.marbles(), vec![5, 1, 3, 0, 4, 2]);
+    circle.rotate(-2);
+    assert_eq!(circle.current(), 4);
+    assert_eq!(circle.remove(), 4);
+    assert_eq!(circle.current(), 2);
+    assert_eq!(circle.marbles(), vec![2, 5, 1, 3, 0]);
+}
@@ -0,0 +1,146 @@
+use crate::circle::Circle;
+
+// The numbers that define the game. Normally each marble is placed `place_offset`
+// marbles clockwise of the current one (between the marbles at 1 and 2 places for
+// the puzzle). A multiple of `scoring_multiple` is kept by its player instead, along
+// with the marble `capture_offset` places clockwise of the current one (that is, 7
+// places counter-clockwise for the puzzle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MarbleRules {
+    pub scoring_multiple: usize,
+    pub place_offset: isize,
+    pub capture_offset: isize,
+}
+
+impl Default for MarbleRules {
+    fn default() -> MarbleRules {
+        MarbleRules {
+            scoring_multiple: 23,
+            place_offset: 2,
+            capture_offset: -7,
+        }
+    }
+}
+
+// A marble that is kept by its player, and the marble taken from the circle with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capture {
+    pub player: usize,
+    pub marble: usize,
+    pub removed: usize,
+}
+
+impl Capture {
+    pub fn points(&self) -> u64 {
+        (self.marble + self.removed) as u64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Game {
+    // Indexed by player, counting from 0
+    pub scores: Vec<u64>,
+    // In the order they happened
+    pub captures: Vec<Capture>,
+}
+
+impl Game {
+    pub fn high_score(&self) -> u64 {
+        self.scores.iter().cloned().max().unwrap_or(0)
+    }
+}
+
+// The scoring multiple must be at least 2, so that the circle never runs out of
+// marbles.
+pub fn play(rules: &MarbleRules, players: usize, last_marble: usize) -> Game {
+    assert!(
+        rules.scoring_multiple >= 2,
+        "The scoring multiple must be at least 2"
+    );
+    let mut circle = Circle::new(last_marble);
+    let mut scores = vec![0; players];
+    let mut captures = Vec::new();
+
+    for marble in 1..=last_marble {
+        let player = (marble - 1) % players;
+        if marble % rules.scoring_multiple == 0 {
+            circle.rotate(rules.capture_offset);
+            let capture = Capture {
+                player,
+                marble,
+                removed: circle.remove(),
+            };
+            scores[player] += capture.points();
+            captures.push(capture);
+        } else {
+            // Inserting goes after the current marble
+            circle.rotate(rules.place_offset - 1);
+            circle.insert(marble);
+        }
+    }
+    Game { scores, captures }
+}
+
+#[test]
+fn test() {
+    let rules = MarbleRules::default();
+    let game = play(&rules, 9, 25);
+    assert_eq!(game.high_score(), 32);
+    assert_eq!(game.scores[4], 32);
+    assert_eq!(
+        game.captures,
+        vec![Capture {
+            player: 4,
+            marble: 23,
+            removed: 9
+        }]
+    );
+
+    let game = play(&rules, 10, 1618);
+    assert_eq!(game.high_score(), 8317);
+    assert_eq!(game.captures.len(), 1618 / 23);
+    assert_eq!(
+        game.scores.iter().sum::<u64>(),
+        game.captures.iter().map(Capture::points).sum::<u64>()
+    );
+}
+
+// The game played the slow way, the way this program used to: the current marble is
+// always at the front of a VecDeque, which is rotated around it.
+#[cfg(test)]
+fn play_slowly(rules: &MarbleRules, players: usize, last_marble: usize) -> Vec<u64> {
+    use std::collections::VecDeque;
+
+    let rotate = |ring: &mut VecDeque<usize>, steps: isize| {
+        let steps = steps.rem_euclid(ring.len() as isize) as usize;
+        ring.rotate_left(steps);
+    };
+    let mut ring = VecDeque::from(vec![0]);
+    let mut scores = vec![0; players];
+    for marble in 1..=last_marble {
+        if marble % rules.scoring_multiple == 0 {
+            rotate(&mut ring, rules.capture_offset);
+            scores[(marble - 1) % players] += (marble + ring.pop_front().unwrap()) as u64;
+        } else {
+            rotate(&mut ring, rules.place_offset);
+            ring.push_front(marble);
+        }
+    }
+    scores
+}
+
+#[test]
+fn variants() {
+    for &scoring_multiple in &[2, 3, 5, 23, 31] {
+        for place_offset in -3..=4 {
+            for capture_offset in -9..=3 {
+                let rules = MarbleRules {
+                    scoring_multiple,
+                    place_offset,
+                    capture_offset,
+                };
+                assert_eq!(play(&rules, 7, 500).scores, play_slowly(&rules, 7, 500));
+            }
+        }
+    }
+}
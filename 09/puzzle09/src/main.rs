@@ -1,54 +1,128 @@
-use std::collections::VecDeque;
+use std::env;
 use std::io;
 use std::io::BufRead;
+use std::ops::RangeInclusive;
 
-fn main() {
-    let numbers: Vec<u32> = io::stdin()
-        .lock()
-        .lines()
-        .next()
-        .expect("Error: No lines")
-        .expect("Error: Read error")
-        .split_whitespace()
-        .filter_map(|s| s.parse().ok())
-        .collect();
-
-    println!("{:?}", marblegame(numbers[0] as usize, numbers[1]));
-    println!("{:?}", marblegame(numbers[0] as usize, numbers[1] * 100));
-}
+mod circle;
+mod game;
+use game::MarbleRules;
+
+const USAGE: &str = "Usage: puzzle09 [--scores] [--captures] [--multiple N] [--place N] \
+                     [--capture N] [--players FROM-TO] [--marbles FROM-TO[:STEP]] < input";
 
-fn marblegame(players: usize, last_marble: u32) -> u32 {
-    let mut ring: VecDeque<u32> = VecDeque::new();
-    ring.push_back(0);
-    let mut scores: Vec<u32> = vec![0; players];
-    let mut current_player = 0;
-
-    for marble in 1..=last_marble {
-        if marble % 23 == 0 {
-            cycle(&mut ring, -7);
-            scores[current_player] += marble + ring[0];
-            ring.pop_front();
-        } else {
-            cycle(&mut ring, 2);
-            ring.push_front(marble);
+fn main() {
+    let mut show_scores = false;
+    let mut show_captures = false;
+    let mut rules = MarbleRules::default();
+    let mut sweep_players = None;
+    let mut sweep_marbles = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(USAGE);
+        match arg.as_str() {
+            "--scores" => show_scores = true,
+            "--captures" => show_captures = true,
+            "--multiple" => rules.scoring_multiple = value().parse().expect(USAGE),
+            "--place" => rules.place_offset = value().parse().expect(USAGE),
+            "--capture" => rules.capture_offset = value().parse().expect(USAGE),
+            "--players" => sweep_players = Some(parse_range(&value()).expect(USAGE)),
+            "--marbles" => sweep_marbles = Some(parse_range(&value()).expect(USAGE)),
+            _ => panic!("{}", USAGE),
         }
-        current_player = (current_player + 1) % players;
     }
-    *scores.iter().max().unwrap()
-}
+    if rules.scoring_multiple < 2 {
+        panic!("Error: The scoring multiple must be at least 2");
+    }
 
-fn cycle(vd: &mut VecDeque<u32>, idx: isize) {
-    if vd.len() == 0 {
+    // Sweeping only needs the numbers from the input that aren't being swept
+    let numbers: Vec<usize> = match (&sweep_players, &sweep_marbles) {
+        (Some(_), Some(_)) => Vec::new(),
+        _ => io::stdin()
+            .lock()
+            .lines()
+            .next()
+            .expect("Error: No lines")
+            .expect("Error: Read error")
+            .split_whitespace()
+            .filter_map(|s| s.parse().ok())
+            .collect(),
+    };
+
+    if sweep_players.is_some() || sweep_marbles.is_some() {
+        let players = sweep_players.unwrap_or_else(|| (numbers[0]..=numbers[0], 1));
+        let marbles = sweep_marbles.unwrap_or_else(|| (numbers[1]..=numbers[1], 1));
+        print!("{}", sweep(&rules, players, marbles));
         return;
     }
-    for _ in 0..idx {
-        let e = vd.pop_front().unwrap();
-        vd.push_back(e);
+
+    let (players, last_marble) = (numbers[0], numbers[1]);
+    let game = game::play(&rules, players, last_marble);
+    if show_captures {
+        for c in &game.captures {
+            println!(
+                "Player {} keeps marble {} and takes marble {}: {} points",
+                c.player + 1,
+                c.marble,
+                c.removed,
+                c.points()
+            );
+        }
     }
-    for _ in 0..-idx {
-        let e = vd.pop_back().unwrap();
-        vd.push_front(e);
+    if show_scores {
+        for (player, score) in game.scores.iter().enumerate() {
+            println!("Player {}: {}", player + 1, score);
+        }
     }
+
+    println!("{:?}", game.high_score());
+    println!(
+        "{:?}",
+        game::play(&rules, players, last_marble * 100).high_score()
+    );
+}
+
+// The high score with the puzzle's rules, as in the examples
+#[cfg(test)]
+fn marblegame(players: usize, last_marble: usize) -> u64 {
+    game::play(&MarbleRules::default(), players, last_marble).high_score()
+}
+
+// A range of numbers, with a step: "10-100:10", or just "10-100" to take every one
+fn parse_range(s: &str) -> Option<(RangeInclusive<usize>, usize)> {
+    let (range, step) = match s.split_once(':') {
+        Some((range, step)) => (range, step.parse().ok().filter(|&s| s > 0)?),
+        None => (s, 1),
+    };
+    let (from, to) = range.split_once('-')?;
+    Some((from.parse().ok()?..=to.parse().ok()?, step))
+}
+
+// The high scores for every combination of player count and last marble, one game
+// per line
+fn sweep(
+    rules: &MarbleRules,
+    (players, player_step): (RangeInclusive<usize>, usize),
+    (marbles, marble_step): (RangeInclusive<usize>, usize),
+) -> String {
+    let mut out = String::from("players  last marble  high score  winner\n");
+    for p in players.step_by(player_step).filter(|&p| p > 0) {
+        for m in marbles.clone().step_by(marble_step) {
+            let game = game::play(rules, p, m);
+            let high_score = game.high_score();
+            let winners: Vec<String> = (0..p)
+                .filter(|&player| high_score > 0 && game.scores[player] == high_score)
+                .map(|player| (player + 1).to_string())
+                .collect();
+            out += &format!(
+                "{:>7}  {:>11}  {:>10}  {}\n",
+                p,
+                m,
+                high_score,
+                winners.join(",")
+            );
+        }
+    }
+    out
 }
 
 #[test]
@@ -60,3 +134,16 @@ fn test() {
     assert_eq!(marblegame(21, 6111), 54718);
     assert_eq!(marblegame(30, 5807), 37305);
 }
+
+#[test]
+fn test_sweep() {
+    assert_eq!(parse_range("10-100:10"), Some((10..=100, 10)));
+    assert_eq!(parse_range("5-6"), Some((5..=6, 1)));
+    assert_eq!(parse_range("5-6:0"), None);
+
+    let table = sweep(&MarbleRules::default(), (9..=10, 1), (25..=1618, 1593));
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[1], "      9           25          32  5");
+    assert_eq!(lines[4], "     10         1618        8317  10");
+}
@@ -1,82 +1,56 @@
-use regex::Regex;
-use std::cmp::max;
-use std::cmp::min;
-use std::fs::File;
+use std::env;
 use std::io;
-use std::io::BufRead;
-use std::io::BufReader;
-use std::io::Write;
+use std::io::Read;
+use std::path::PathBuf;
 
-#[macro_use]
-extern crate text_io;
+mod ocr;
+mod render;
+mod sky;
+use sky::Sky;
 
-fn main() {
-    let re = Regex::new(r"-?\d+").unwrap();
-    let f = File::open("input").unwrap();
-    let f = BufReader::new(f);
-    let mut data: Vec<Vec<i32>> = f
-        .lines()
-        .filter_map(Result::ok)
-        .map(|s| {
-            re.captures_iter(&s)
-                .map(|m| m.get(0).unwrap().as_str().parse().unwrap())
-                .collect()
-        })
-        .collect();
+const USAGE: &str = "Usage: puzzle10 [--exact | --width N --height N] \
+                     [--frames DIR [--around SECONDS]] < input";
 
-    let mut time = 0;
-    loop {
-        display(&data);
-        print!(
-            "Time is {:}. Advance how many seconds forward or backward? ",
-            time
-        );
-        let _ = io::stdout().flush();
-        let i: i32;
-        scan!("{}", i);
-        time += i;
-        advance(&mut data, i);
+fn main() {
+    let mut exact = false;
+    let (mut width, mut height) = (80, 25);
+    let mut frames_dir = None;
+    let mut around = 10;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(USAGE);
+        match arg.as_str() {
+            "--exact" => exact = true,
+            "--width" => width = value().parse().expect(USAGE),
+            "--height" => height = value().parse().expect(USAGE),
+            "--frames" => frames_dir = Some(PathBuf::from(value())),
+            "--around" => around = value().parse().expect(USAGE),
+            _ => panic!("{}", USAGE),
+        }
     }
-}
 
-fn advance(point_data: &mut Vec<Vec<i32>>, factor: i32) {
-    for v in point_data.iter_mut() {
-        v[0] += factor * v[2];
-        v[1] += factor * v[3];
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .expect("Error: Read error");
+    let sky = Sky::parse(&input);
+
+    let (time, message) = sky.message(width, height);
+    if exact {
+        print!("{}", render::exact(&sky.at(time)));
+    } else {
+        print!("{}", message);
     }
-}
-
-const SCREENW: usize = 62;
-const SCREENH: usize = 10;
-
-fn display(point_data: &Vec<Vec<i32>>) {
-    let mut screen = vec![' '; SCREENW * SCREENH];
-    let (xmin, ymin, xmax, ymax) = point_data.iter().fold(
-        (std::i32::MAX, std::i32::MAX, std::i32::MIN, std::i32::MIN),
-        |(a, b, c, d), v| (min(a, v[0]), min(b, v[1]), max(c, v[0]), max(d, v[1])),
-    );
-    println!(
-        "Display is LT {:?}, RB {:?}, that is {:} x {:}",
-        (xmin, ymin),
-        (xmax, ymax),
-        xmax - xmin + 1,
-        ymax - ymin + 1
-    );
-
-    for v in point_data.iter() {
-        let x = rescale(v[0], xmin, xmax, 0, SCREENW as i32 - 1);
-        let y = rescale(v[1], ymin, ymax, 0, SCREENH as i32 - 1);
-        screen[(y as usize * SCREENW + x as usize)] = '*';
+    match ocr::read(&sky.at(time)) {
+        Ok(text) => println!("{}", text),
+        Err(e) => eprintln!("Could not read the message: {:?}", e),
     }
+    println!("{:?}", time);
 
-    for y in 0..SCREENH {
-        for x in 0..SCREENW {
-            print!("{:}", screen[y * SCREENW + x]);
-        }
-        println!("");
+    if let Some(dir) = frames_dir {
+        let times = (time - around).max(0)..=time + around;
+        let count = render::frames(&dir, times, |t| sky.at(t), 1000)
+            .expect("Error: Could not write the frames");
+        eprintln!("Wrote {} frames to {}", count, dir.display());
     }
 }
-
-fn rescale(x: i32, froma: i32, fromb: i32, toa: i32, tob: i32) -> i32 {
-    (x - froma) * (tob - toa) / (fromb - froma) + toa
-}
@@ -0,0 +1,184 @@
+use crate::sky::{Bounds, Point};
+use std::collections::BTreeSet;
+
+// The letters of the 6 wide, 10 high font used by this puzzle, side by side
+const LARGE_LETTERS: &str = "ABCEFGHJKLNPRXZ";
+const LARGE: [&str; 10] = [
+    "..##.. #####. .####. ###### ###### .####. #....# ...### #....# #..... #....# #####. #####. #....# ######",
+    ".#..#. #....# #....# #..... #..... #....# #....# ....#. #...#. #..... ##...# #....# #....# #....# .....#",
+    "#....# #....# #..... #..... #..... #..... #....# ....#. #..#.. #..... ##...# #....# #....# .#..#. .....#",
+    "#....# #....# #..... #..... #..... #..... #....# ....#. #.#... #..... #.#..# #....# #....# .#..#. ....#.",
+    "#....# #####. #..... #####. #####. #..... ###### ....#. ##.... #..... #.#..# #####. #####. ..##.. ...#..",
+    "###### #....# #..... #..... #..... #..### #....# ....#. ##.... #..... #..#.# #..... #..#.. ..##.. ..#...",
+    "#....# #....# #..... #..... #..... #....# #....# ....#. #.#... #..... #..#.# #..... #...#. .#..#. .#....",
+    "#....# #....# #..... #..... #..... #....# #....# #...#. #..#.. #..... #...## #..... #...#. .#..#. #.....",
+    "#....# #....# #....# #..... #..... #...## #....# #...#. #...#. #..... #...## #..... #....# #....# #.....",
+    "#....# #####. .####. ###### #..... .###.# #....# .###.. #....# ###### #....# #..... #....# #....# ######",
+];
+
+// The letters of the 4 wide, 6 high font used by some other puzzles, side by side
+const SMALL_LETTERS: &str = "ABCEFGHIJKLOPRSUZ";
+const SMALL: [&str; 6] = [
+    ".##. ###. .##. #### #### .##. #..# .### ..## #..# #... .##. ###. ###. .### #..# ####",
+    "#..# #..# #..# #... #... #..# #..# ..#. ...# #.#. #... #..# #..# #..# #... #..# ...#",
+    "#..# ###. #... ###. ###. #... #### ..#. ...# ##.. #... #..# #..# #..# #... #..# ..#.",
+    "#### #..# #... #... #... #.## #..# ..#. ...# #.#. #... #..# ###. ###. .##. #..# .#..",
+    "#..# #..# #..# #... #... #..# #..# ..#. #..# #.#. #... #..# #... #.#. ...# #..# #...",
+    "#..# ###. .##. #### #... .### #..# .### .##. #..# #### .##. #... #..# ###. .##. ####",
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    NoStars,
+    // Neither font is this many rows high
+    UnsupportedHeight(i64),
+    // The message as far as it could be read, with '?' for every glyph that wasn't
+    // recognised, and those glyphs drawn out
+    UnknownGlyphs(String, Vec<String>),
+}
+
+// A glyph as the rows of its occupied columns, so that glyphs with empty columns at
+// the sides (like the small I) compare equal however they were cut out
+fn trim(rows: &[String]) -> Vec<String> {
+    let width = rows.iter().map(String::len).max().unwrap_or(0);
+    let used = |c: usize| rows.iter().any(|r| used_at(r, c));
+    let (first, last) = match (
+        (0..width).find(|&c| used(c)),
+        (0..width).rfind(|&c| used(c)),
+    ) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Vec::new(),
+    };
+    rows.iter()
+        .map(|r| {
+            (first..=last)
+                .map(|c| if used_at(r, c) { '#' } else { '.' })
+                .collect()
+        })
+        .collect()
+}
+
+fn used_at(row: &str, column: usize) -> bool {
+    row.as_bytes().get(column) == Some(&b'#')
+}
+
+// The glyphs of a font, as rows of '#' and '.'
+fn glyphs(letters: &str, rows: &[&str]) -> Vec<(char, Vec<String>)> {
+    letters
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let glyph = rows
+                .iter()
+                .map(|r| r.split(' ').nth(i).unwrap().to_owned())
+                .collect();
+            (c, glyph)
+        })
+        .collect()
+}
+
+fn font(height: i64) -> Option<Vec<(char, Vec<String>)>> {
+    let glyphs = match height {
+        10 => glyphs(LARGE_LETTERS, &LARGE),
+        6 => glyphs(SMALL_LETTERS, &SMALL),
+        _ => return None,
+    };
+    Some(glyphs.into_iter().map(|(c, g)| (c, trim(&g))).collect())
+}
+
+// Reads a message written by the stars. The font is chosen by the height of the
+// message, and the glyphs are separated by the columns without any stars.
+pub fn read(points: &[Point]) -> Result<String, OcrError> {
+    let bounds = Bounds::of(points).ok_or(OcrError::NoStars)?;
+    let font = font(bounds.height()).ok_or_else(|| OcrError::UnsupportedHeight(bounds.height()))?;
+    let stars: BTreeSet<Point> = points.iter().cloned().collect();
+    let used = |x: i64| (bounds.min.1..=bounds.max.1).any(|y| stars.contains(&(x, y)));
+
+    let mut text = String::new();
+    let mut unknown = Vec::new();
+    let mut x = bounds.min.0;
+    while x <= bounds.max.0 {
+        if !used(x) {
+            x += 1;
+            continue;
+        }
+        let start = x;
+        while x <= bounds.max.0 && used(x) {
+            x += 1;
+        }
+        let rows: Vec<String> = (bounds.min.1..=bounds.max.1)
+            .map(|y| {
+                (start..x)
+                    .map(|x| if stars.contains(&(x, y)) { '#' } else { '.' })
+                    .collect()
+            })
+            .collect();
+        match font.iter().find(|(_, glyph)| *glyph == rows) {
+            Some(&(c, _)) => text.push(c),
+            None => {
+                text.push('?');
+                unknown.push(rows.join("\n") + "\n");
+            }
+        }
+    }
+
+    if unknown.is_empty() {
+        Ok(text)
+    } else {
+        Err(OcrError::UnknownGlyphs(text, unknown))
+    }
+}
+
+#[cfg(test)]
+fn write(text: &str, font: &[(char, Vec<String>)], spacing: i64) -> Vec<Point> {
+    let mut points = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let rows = &font.iter().find(|(f, _)| *f == c).unwrap().1;
+        let left = i as i64 * (rows[0].len() as i64 + spacing);
+        for (y, row) in rows.iter().enumerate() {
+            for (x, _) in row.char_indices().filter(|&(_, p)| p == '#') {
+                points.push((left + x as i64, y as i64));
+            }
+        }
+    }
+    points
+}
+
+#[test]
+fn test() {
+    let sky = crate::sky::Sky::parse(include_str!("../../input"));
+    let time = sky.convergence();
+    assert_eq!(read(&sky.at(time)), Ok("AJZNXHKE".to_owned()));
+
+    let large = glyphs(LARGE_LETTERS, &LARGE);
+    let small = glyphs(SMALL_LETTERS, &SMALL);
+    assert_eq!(
+        read(&write(LARGE_LETTERS, &large, 2)),
+        Ok(LARGE_LETTERS.to_owned())
+    );
+    assert_eq!(
+        read(&write(SMALL_LETTERS, &small, 1)),
+        Ok(SMALL_LETTERS.to_owned())
+    );
+    assert_eq!(read(&write("IZI", &small, 1)), Ok("IZI".to_owned()));
+
+    // The example's message is only 8 rows high
+    let example = crate::sky::Sky::parse(include_str!("../../input2"));
+    assert_eq!(read(&example.at(3)), Err(OcrError::UnsupportedHeight(8)));
+    assert_eq!(read(&[]), Err(OcrError::NoStars));
+
+    // An H with a crossbar one row too low
+    let mut points = write("AHA", &large, 2);
+    points.retain(|&(x, y)| !(9..=12).contains(&x) || y != 4);
+    points.extend((9..=12).map(|x| (x, 5)));
+    assert_eq!(
+        read(&points),
+        Err(OcrError::UnknownGlyphs(
+            "A?A".to_owned(),
+            vec![
+                "#....#\n#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n"
+                    .to_owned()
+            ]
+        ))
+    );
+}
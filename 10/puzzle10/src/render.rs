@@ -0,0 +1,116 @@
+use crate::sky::{Bounds, Point};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// Which cells of a grid covering the given area contain a star. When the area is
+// more than `max_width` by `max_height` positions, every cell covers a block of
+// positions, the same number for every cell in a row or column, just enough to fit.
+fn cells(points: &[Point], area: Bounds, max_width: usize, max_height: usize) -> Vec<Vec<bool>> {
+    let scale = |size: i64, max: usize| (size as u64).div_ceil(max.max(1) as u64).max(1) as i64;
+    let (scale_x, scale_y) = (
+        scale(area.width(), max_width),
+        scale(area.height(), max_height),
+    );
+    let width = (area.width() + scale_x - 1) / scale_x;
+    let height = (area.height() + scale_y - 1) / scale_y;
+
+    let mut grid = vec![vec![false; width as usize]; height as usize];
+    for &(x, y) in points {
+        if area.min.0 <= x && x <= area.max.0 && area.min.1 <= y && y <= area.max.1 {
+            let (col, row) = ((x - area.min.0) / scale_x, (y - area.min.1) / scale_y);
+            grid[row as usize][col as usize] = true;
+        }
+    }
+    grid
+}
+
+fn text(grid: &[Vec<bool>]) -> String {
+    grid.iter()
+        .map(|row| {
+            let line: String = row.iter().map(|&c| if c { '#' } else { '.' }).collect();
+            line + "\n"
+        })
+        .collect()
+}
+
+// One character per position in the bounding box: '#' where there is a star, '.'
+// elsewhere, with a newline after every row
+pub fn exact(points: &[Point]) -> String {
+    match Bounds::of(points) {
+        Some(b) => text(&cells(points, b, b.width() as usize, b.height() as usize)),
+        None => String::new(),
+    }
+}
+
+// Like exact when the stars fit in `max_width` by `max_height` characters. If not,
+// each character stands for a block of positions, and shows '#' if any of them has
+// a star.
+pub fn fit(points: &[Point], max_width: usize, max_height: usize) -> String {
+    match Bounds::of(points) {
+        Some(b) => text(&cells(points, b, max_width, max_height)),
+        None => String::new(),
+    }
+}
+
+// A binary PPM image of the given area, with white stars on black, scaled down like
+// `fit` if needed
+pub fn ppm(points: &[Point], area: Bounds, max_width: usize, max_height: usize) -> Vec<u8> {
+    let grid = cells(points, area, max_width, max_height);
+    let (width, height) = (grid.first().map_or(0, Vec::len), grid.len());
+    let mut image = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    for &cell in grid.iter().flatten() {
+        let value = if cell { 255 } else { 0 };
+        image.extend_from_slice(&[value; 3]);
+    }
+    image
+}
+
+// Writes frame_0000.ppm, frame_0001.ppm and so on to `dir`, one for every second in
+// `times`. All frames show the same area, large enough for the stars in each of them.
+pub fn frames<I>(
+    dir: &Path,
+    times: I,
+    stars_at: impl Fn(i64) -> Vec<Point>,
+    max_size: usize,
+) -> io::Result<usize>
+where
+    I: IntoIterator<Item = i64>,
+{
+    let frames: Vec<Vec<Point>> = times.into_iter().map(stars_at).collect();
+    let all: Vec<Point> = frames.iter().flatten().cloned().collect();
+    let area = match Bounds::of(&all) {
+        Some(area) => area,
+        None => return Ok(0),
+    };
+    fs::create_dir_all(dir)?;
+    for (i, points) in frames.iter().enumerate() {
+        let image = ppm(points, area, max_size, max_size);
+        fs::write(dir.join(format!("frame_{:04}.ppm", i)), image)?;
+    }
+    Ok(frames.len())
+}
+
+#[test]
+fn test() {
+    let points = [(10, 5), (12, 5), (11, 6)];
+    assert_eq!(exact(&points), "#.#\n.#.\n");
+    assert_eq!(fit(&points, 3, 2), exact(&points));
+    assert_eq!(fit(&points, 2, 2), "##\n#.\n");
+    assert_eq!(fit(&points, 1, 1), "#\n");
+    assert_eq!(fit(&[], 10, 10), "");
+
+    // All stars in one row or column used to divide by zero
+    assert_eq!(fit(&[(3, 7), (9, 7)], 4, 4), "#..#\n");
+    assert_eq!(fit(&[(0, 0), (0, 1000)], 4, 4), "#\n.\n.\n#\n");
+
+    // Frames share an area larger than their own stars, scaled to at most 10 by 5
+    let area = Bounds {
+        min: (0, 0),
+        max: (29, 19),
+    };
+    let grid = cells(&points, area, 10, 5);
+    assert_eq!((grid[0].len(), grid.len()), (10, 5));
+    assert!(grid[1][3] && grid[1][4]);
+    assert_eq!(grid.iter().flatten().filter(|&&c| c).count(), 2);
+}
@@ -0,0 +1,170 @@
+use regex::Regex;
+
+pub type Point = (i64, i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Star {
+    pub position: Point,
+    pub velocity: Point,
+}
+
+impl Star {
+    pub fn at(&self, time: i64) -> Point {
+        (
+            self.position.0 + time * self.velocity.0,
+            self.position.1 + time * self.velocity.1,
+        )
+    }
+}
+
+// The smallest rectangle containing a set of points, both corners inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    pub fn of(points: &[Point]) -> Option<Bounds> {
+        let first = *points.first()?;
+        Some(points.iter().fold(
+            Bounds {
+                min: first,
+                max: first,
+            },
+            |b, &(x, y)| Bounds {
+                min: (b.min.0.min(x), b.min.1.min(y)),
+                max: (b.max.0.max(x), b.max.1.max(y)),
+            },
+        ))
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max.0 - self.min.0 + 1
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.1 - self.min.1 + 1
+    }
+
+    pub fn area(&self) -> i128 {
+        self.width() as i128 * self.height() as i128
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Sky {
+    pub stars: Vec<Star>,
+}
+
+impl Sky {
+    // Takes the four numbers from every line with any: "position=< 9,  1> velocity=< 0,  2>"
+    pub fn parse(s: &str) -> Sky {
+        let re = Regex::new(r"-?\d+").unwrap();
+        let stars = s
+            .lines()
+            .filter_map(|line| {
+                let n: Vec<i64> = re
+                    .find_iter(line)
+                    .map(|m| m.as_str().parse().unwrap())
+                    .collect();
+                match n[..] {
+                    [x, y, dx, dy] => Some(Star {
+                        position: (x, y),
+                        velocity: (dx, dy),
+                    }),
+                    _ => None,
+                }
+            })
+            .collect();
+        Sky { stars }
+    }
+
+    pub fn at(&self, time: i64) -> Vec<Point> {
+        self.stars.iter().map(|s| s.at(time)).collect()
+    }
+
+    pub fn bounds(&self, time: i64) -> Option<Bounds> {
+        Bounds::of(&self.at(time))
+    }
+
+    // The second (not before 0) at which the stars are closest together, that is when
+    // their bounding box has the smallest area.
+    //
+    // Each side of the box is the maximum or minimum of linear functions of time, so
+    // its width plus height is convex. Binary search on its slope finds the second
+    // where that stops decreasing. The area isn't quite convex, so from there walk
+    // to the nearest second where the area is minimal.
+    pub fn convergence(&self) -> i64 {
+        let size = |t: i64| self.bounds(t).map_or(0, |b| b.width() + b.height());
+        let area = |t: i64| self.bounds(t).map_or(0, |b| b.area());
+
+        // Find an upper limit by doubling, then the first second after which the size
+        // doesn't decrease
+        let mut high = 1;
+        while size(high + 1) < size(high) {
+            high *= 2;
+        }
+        let mut low = 0;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if size(mid + 1) < size(mid) {
+                low = mid + 1;
+            } else {
+                high = mid;
+            }
+        }
+
+        let mut time = low;
+        while time > 0 && area(time - 1) < area(time) {
+            time -= 1;
+        }
+        while area(time + 1) < area(time) {
+            time += 1;
+        }
+        time
+    }
+
+    // The time the message appears, and the message itself, scaled down if it
+    // doesn't fit in the given number of characters
+    pub fn message(&self, max_width: usize, max_height: usize) -> (i64, String) {
+        let time = self.convergence();
+        (
+            time,
+            crate::render::fit(&self.at(time), max_width, max_height),
+        )
+    }
+}
+
+#[test]
+fn test() {
+    let sky = Sky::parse(include_str!("../../input2"));
+    assert_eq!(sky.stars.len(), 31);
+    assert_eq!(
+        sky.stars[0],
+        Star {
+            position: (9, 1),
+            velocity: (0, 2)
+        }
+    );
+    assert_eq!(
+        sky.bounds(3).map(|b| (b.width(), b.height())),
+        Some((10, 8))
+    );
+
+    let (time, message) = sky.message(80, 25);
+    assert_eq!(time, 3);
+    assert_eq!(
+        message,
+        "\
+#...#..###
+#...#...#.
+#...#...#.
+#####...#.
+#...#...#.
+#...#...#.
+#...#...#.
+#...#..###
+"
+    );
+}
@@ -0,0 +1,44 @@
+use crate::PowerGrid;
+
+// A binary PPM image of the power levels, one pixel per fuel cell. Levels go from
+// blue at -5 through black at 0 to red at 4, and an optional square (top left cell
+// and size, as the answers give them) is outlined in white.
+pub fn ppm(grid: &PowerGrid, highlight: Option<(usize, usize, usize)>) -> Vec<u8> {
+    let mut image = format!("P6\n{} {}\n255\n", grid.size, grid.size).into_bytes();
+    for y in 1..=grid.size {
+        for x in 1..=grid.size {
+            let pixel = match highlight {
+                Some(square) if on_outline(square, x, y) => [255, 255, 255],
+                _ => colour(grid.power_at(x, y)),
+            };
+            image.extend_from_slice(&pixel);
+        }
+    }
+    image
+}
+
+fn colour(level: i32) -> [u8; 3] {
+    if level < 0 {
+        [0, 0, (-level * 51) as u8]
+    } else {
+        [(level * 63) as u8, 0, 0]
+    }
+}
+
+fn on_outline((hx, hy, bs): (usize, usize, usize), x: usize, y: usize) -> bool {
+    let inside = (hx..hx + bs).contains(&x) && (hy..hy + bs).contains(&y);
+    inside && (x == hx || y == hy || x == hx + bs - 1 || y == hy + bs - 1)
+}
+
+#[test]
+fn test() {
+    // The full range of power levels, from brightest blue through black to red
+    assert_eq!(colour(-5), [0, 0, 255]);
+    assert_eq!(colour(0), [0, 0, 0]);
+    assert_eq!(colour(4), [252, 0, 0]);
+
+    // Only the edge of the highlighted square is outlined
+    let square = (2, 2, 3);
+    assert!(on_outline(square, 2, 2) && on_outline(square, 4, 3));
+    assert!(!on_outline(square, 3, 3) && !on_outline(square, 5, 2));
+}
@@ -1,10 +1,43 @@
+use rayon::prelude::*;
+use std::cmp::Reverse;
 use std::default::Default;
+use std::env;
+use std::fs;
 use std::io;
 use std::io::BufRead;
 
+mod image;
+mod query;
+
 const GRIDSZ: usize = 300;
 
+const USAGE: &str = "Usage: puzzle11 [--size N] [--top K [--top-size N]] [--containing X,Y] \
+                     [--window WxH] [--image FILE] < input";
+
 fn main() {
+    let mut grid_size = GRIDSZ;
+    let mut top = None;
+    let mut top_size = 3;
+    let mut containing = None;
+    let mut window = None;
+    let mut image_file = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(USAGE);
+        match arg.as_str() {
+            "--size" => grid_size = value().parse().ok().filter(|&s| s > 0).expect(USAGE),
+            "--top" => top = Some(value().parse::<usize>().expect(USAGE)),
+            "--top-size" => top_size = value().parse().ok().filter(|&s| s > 0).expect(USAGE),
+            "--containing" => containing = Some(pair(&value(), ',').expect(USAGE)),
+            "--window" => {
+                let size = pair(&value(), 'x').filter(|&(w, h)| w > 0 && h > 0);
+                window = Some(size.expect(USAGE))
+            }
+            "--image" => image_file = Some(value()),
+            _ => panic!("{}", USAGE),
+        }
+    }
+
     let input: usize = io::stdin()
         .lock()
         .lines()
@@ -14,8 +47,36 @@ fn main() {
         .parse()
         .expect("Error: type a number");
 
-    println!("{:?}", max_power_square(3, input));
-    println!("{:?}", max_power(input));
+    let grid = PowerGrid::new(grid_size, input);
+    let square = grid.max_power_square(3.min(grid_size));
+    let best = grid.max_power();
+    println!("{:?}", square);
+    println!("{:?}", best);
+
+    if let Some(k) = top {
+        for (x, y, pow) in grid.top_squares(top_size, k) {
+            println!("{},{}: {}", x, y, pow);
+        }
+    }
+    if let Some((x, y)) = containing {
+        if x < 1 || y < 1 || x > grid_size || y > grid_size {
+            panic!("Error: ({}, {}) is not in the grid", x, y);
+        }
+        println!("{:?}", grid.best_square_containing(x, y));
+    }
+    if let Some((w, h)) = window {
+        println!("{:?}", grid.max_power_window(w, h));
+    }
+    if let Some(file) = image_file {
+        let image = image::ppm(&grid, Some((best.0, best.1, best.2)));
+        fs::write(&file, image).expect("Error: Could not write the image");
+    }
+}
+
+// Two numbers with a separator between them: "3,5" or "3x5"
+fn pair(s: &str, separator: char) -> Option<(usize, usize)> {
+    let (a, b) = s.split_once(separator)?;
+    Some((a.parse().ok()?, b.parse().ok()?))
 }
 
 fn power(x: usize, y: usize, serial: usize) -> i32 {
@@ -26,56 +87,93 @@ fn power(x: usize, y: usize, serial: usize) -> i32 {
     power_level as i32 - 5
 }
 
-fn max_power_square(block_size: usize, serial: usize) -> (usize, usize, i32) {
-    // Precompute the partial sum grid
-    let mut partial_sum_grid = vec![0; GRIDSZ * GRIDSZ];
-    for y in 0..GRIDSZ {
-        for x in 0..GRIDSZ {
-            partial_sum_grid[y * GRIDSZ + x] =
-                power(x + 1, y + 1, serial) - partial_sum(&partial_sum_grid, x, y, 1);
+// The power levels of a square grid of fuel cells, as a summed-area table: each entry
+// is the total power of the rectangle from the top left cell up to and including it.
+// Coordinates in the table count from 0, those of the cells from 1.
+struct PowerGrid {
+    size: usize,
+    partial_sums: Vec<i32>,
+}
+
+impl PowerGrid {
+    fn new(size: usize, serial: usize) -> PowerGrid {
+        let mut partial_sums = vec![0; size * size];
+        for y in 0..size {
+            for x in 0..size {
+                partial_sums[y * size + x] =
+                    power(x + 1, y + 1, serial) - partial_sum(&partial_sums, size, x, y, 1);
+            }
         }
+        PowerGrid { size, partial_sums }
     }
 
-    let mut maximum_power = (0, 0, std::i32::MIN);
+    // The top left cell and total power of the most powerful square of this size,
+    // the first one in reading order if there are several
+    fn max_power_square(&self, block_size: usize) -> (usize, usize, i32) {
+        let mut maximum_power = (0, 0, i32::MIN);
 
-    for y in 0..(GRIDSZ - block_size + 1) {
-        for x in 0..(GRIDSZ - block_size + 1) {
-            let current_power = partial_sum(&partial_sum_grid, x, y, block_size);
+        for y in 0..(self.size - block_size + 1) {
+            for x in 0..(self.size - block_size + 1) {
+                let current_power = partial_sum(&self.partial_sums, self.size, x, y, block_size);
 
-            if current_power > maximum_power.2 {
-                maximum_power = (x + 1, y + 1, current_power);
+                if current_power > maximum_power.2 {
+                    maximum_power = (x + 1, y + 1, current_power);
+                }
             }
         }
+        maximum_power
+    }
+
+    // The most powerful square of any size, preferring the smallest size if several
+    // are equally powerful. The sizes are searched in parallel.
+    fn max_power(&self) -> (usize, usize, usize, i32) {
+        (1..=self.size)
+            .into_par_iter()
+            .map(|bs| {
+                let (x, y, pow) = self.max_power_square(bs);
+                (x, y, bs, pow)
+            })
+            .max_by_key(|&(_, _, bs, pow)| (pow, Reverse(bs)))
+            .unwrap()
     }
-    maximum_power
 }
 
+// The answers for the puzzle's grid size
+#[cfg(test)]
+fn max_power_square(block_size: usize, serial: usize) -> (usize, usize, i32) {
+    PowerGrid::new(GRIDSZ, serial).max_power_square(block_size)
+}
+
+#[cfg(test)]
 fn max_power(input: usize) -> (usize, usize, usize, i32) {
-    let mut maximum_power = (0, 0, 0, std::i32::MIN);
-    for bs in 1..=300 {
-        let (x, y, pow) = max_power_square(bs, input);
-        if pow > maximum_power.3 {
-            maximum_power = (x, y, bs, pow);
-        }
-    }
-    maximum_power
+    PowerGrid::new(GRIDSZ, input).max_power()
 }
 
-fn lenient_matrix_access<T>(m: &Vec<T>, x: Option<usize>, y: Option<usize>) -> T
+fn lenient_matrix_access<T>(m: &[T], width: usize, x: Option<usize>, y: Option<usize>) -> T
 where
     T: std::default::Default + std::marker::Copy,
 {
     match (x, y) {
-        (Some(x), Some(y)) => m[y * GRIDSZ + x],
+        (Some(x), Some(y)) => m[y * width + x],
         _ => Default::default(),
     }
 }
 
-fn partial_sum(m: &Vec<i32>, x: usize, y: usize, block_size: usize) -> i32 {
-    lenient_matrix_access(m, Some(x + block_size - 1), Some(y + block_size - 1))
-        - lenient_matrix_access(m, Some(x + block_size - 1), y.checked_sub(1))
-        - lenient_matrix_access(m, x.checked_sub(1), Some(y + block_size - 1))
-        + lenient_matrix_access(m, x.checked_sub(1), y.checked_sub(1))
+fn partial_sum(m: &[i32], width: usize, x: usize, y: usize, block_size: usize) -> i32 {
+    window_sum(m, width, x, y, block_size, block_size)
+}
+
+// The sum over a rectangle with its top left corner at (x, y), from a summed-area
+// table that is `width` wide. An empty rectangle adds up to 0.
+fn window_sum(m: &[i32], width: usize, x: usize, y: usize, w: usize, h: usize) -> i32 {
+    if w == 0 || h == 0 {
+        return 0;
+    }
+    let access = |x, y| lenient_matrix_access(m, width, x, y);
+    access(Some(x + w - 1), Some(y + h - 1))
+        - access(Some(x + w - 1), y.checked_sub(1))
+        - access(x.checked_sub(1), Some(y + h - 1))
+        + access(x.checked_sub(1), y.checked_sub(1))
 }
 
 #[test]
@@ -91,3 +189,19 @@ fn test() {
     assert_eq!(max_power(18), (90, 269, 16, 113));
     assert_eq!(max_power(42), (232, 251, 12, 119));
 }
+
+#[test]
+fn test_grid_size() {
+    // A grid too small for the best square of the full size grid
+    let grid = PowerGrid::new(50, 18);
+    let (x, y, bs, pow) = grid.max_power();
+    assert!(x + bs - 1 <= 50 && y + bs - 1 <= 50);
+    let brute_force: i32 = (x..x + bs)
+        .flat_map(|x| (y..y + bs).map(move |y| power(x, y, 18)))
+        .sum();
+    assert_eq!(brute_force, pow);
+    assert_eq!(
+        PowerGrid::new(1, 18).max_power(),
+        (1, 1, 1, power(1, 1, 18))
+    );
+}
@@ -0,0 +1,106 @@
+use crate::{window_sum, PowerGrid};
+use rayon::prelude::*;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+impl PowerGrid {
+    // The power level of a single cell, counting from 1
+    pub fn power_at(&self, x: usize, y: usize) -> i32 {
+        self.window_power(x, y, 1, 1)
+    }
+
+    // The total power of a window with its top left cell at (x, y), counting from 1
+    pub fn window_power(&self, x: usize, y: usize, width: usize, height: usize) -> i32 {
+        window_sum(&self.partial_sums, self.size, x - 1, y - 1, width, height)
+    }
+
+    // The k most powerful squares of a size, most powerful first, as top left cell
+    // and total power. Equally powerful squares come in reading order.
+    pub fn top_squares(&self, block_size: usize, k: usize) -> Vec<(usize, usize, i32)> {
+        let mut top = BinaryHeap::with_capacity(k + 1);
+        if k == 0 || block_size == 0 || block_size > self.size {
+            return Vec::new();
+        }
+        for y in 1..=self.size - block_size + 1 {
+            for x in 1..=self.size - block_size + 1 {
+                // The heap keeps the worst square on top, so that it can be dropped
+                // as soon as there are more than k
+                let pow = self.window_power(x, y, block_size, block_size);
+                top.push(Reverse((pow, Reverse((y, x)))));
+                if top.len() > k {
+                    top.pop();
+                }
+            }
+        }
+        top.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse((pow, Reverse((y, x))))| (x, y, pow))
+            .collect()
+    }
+
+    // The most powerful square of any size that contains the given cell, as top left
+    // cell, size and total power. Ties are broken like in max_power.
+    pub fn best_square_containing(&self, x: usize, y: usize) -> (usize, usize, usize, i32) {
+        (1..=self.size)
+            .into_par_iter()
+            .filter_map(|bs| {
+                // Top left corners such that the square contains (x, y) and fits
+                let left = x.saturating_sub(bs - 1).max(1)..=x.min(self.size + 1 - bs);
+                let top = y.saturating_sub(bs - 1).max(1)..=y.min(self.size + 1 - bs);
+                top.flat_map(|sy| left.clone().map(move |sx| (sx, sy)))
+                    .map(|(sx, sy)| (sx, sy, bs, self.window_power(sx, sy, bs, bs)))
+                    .max_by_key(|&(sx, sy, _, pow)| (pow, Reverse((sy, sx))))
+            })
+            .max_by_key(|&(_, _, bs, pow)| (pow, Reverse(bs)))
+            .unwrap()
+    }
+
+    // The most powerful window of the given width and height, like max_power_square
+    pub fn max_power_window(&self, width: usize, height: usize) -> Option<(usize, usize, i32)> {
+        if width == 0 || height == 0 || width > self.size || height > self.size {
+            return None;
+        }
+        let mut maximum_power = None;
+        for y in 1..=self.size - height + 1 {
+            for x in 1..=self.size - width + 1 {
+                let pow = self.window_power(x, y, width, height);
+                if maximum_power.is_none_or(|(_, _, max)| pow > max) {
+                    maximum_power = Some((x, y, pow));
+                }
+            }
+        }
+        maximum_power
+    }
+}
+
+#[test]
+fn test() {
+    let grid = PowerGrid::new(crate::GRIDSZ, 18);
+    assert_eq!(grid.power_at(33, 45), crate::power(33, 45, 18));
+
+    let top = grid.top_squares(3, 3);
+    assert_eq!(top[0], (33, 45, 29));
+    assert_eq!(top.len(), 3);
+    assert!(top[1].2 <= 29 && top[2].2 <= top[1].2);
+    assert_eq!(grid.top_squares(3, 1), vec![grid.max_power_square(3)]);
+
+    // The best square overall contains its own top left cell, and the best square
+    // containing the last cell has to reach the bottom right corner
+    assert_eq!(grid.best_square_containing(90, 269), (90, 269, 16, 113));
+    let (x, y, bs, _) = grid.best_square_containing(300, 300);
+    assert_eq!((x + bs - 1, y + bs - 1), (300, 300));
+
+    assert_eq!(grid.max_power_window(3, 3), Some((33, 45, 29)));
+    let (x, y, pow) = grid.max_power_window(5, 2).unwrap();
+    let brute_force: i32 = (x..x + 5)
+        .flat_map(|x| (y..y + 2).map(move |y| crate::power(x, y, 18)))
+        .sum();
+    assert_eq!(pow, brute_force);
+    assert_eq!(grid.max_power_window(301, 1), None);
+
+    // Nothing fits a square or window without cells
+    assert_eq!(grid.max_power_window(0, 3), None);
+    assert_eq!(grid.top_squares(0, 3), vec![]);
+    assert_eq!(grid.window_power(5, 5, 3, 0), 0);
+    assert_eq!(grid.top_squares(16, 1), vec![(90, 269, 113)]);
+}
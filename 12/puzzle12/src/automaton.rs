@@ -0,0 +1,297 @@
+// One-dimensional cellular automata with two states, where each cell's next state
+// depends on the cells up to `radius` away on either side.
+
+// The next state for every neighbourhood. A neighbourhood is numbered by reading its
+// cells from left to right as a binary number, so with radius 2 the neighbourhood
+// #..#. is number 18.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    radius: usize,
+    table: Vec<bool>,
+}
+
+impl Rule {
+    // The table needs an entry for each of the 2^(2 * radius + 1) neighbourhoods
+    pub fn new(radius: usize, table: Vec<bool>) -> Rule {
+        assert!(
+            radius < 8,
+            "Neighbourhoods of more than 15 cells are not supported"
+        );
+        assert_eq!(table.len(), 1 << (2 * radius + 1));
+        Rule { radius, table }
+    }
+
+    // Wolfram's numbering of the rules with radius 1: bit n of the number is the next
+    // state for neighbourhood n. Rule 90 makes the Sierpinski triangle.
+    pub fn elementary(number: u8) -> Rule {
+        Rule::new(1, (0..8).map(|n| number >> n & 1 == 1).collect())
+    }
+
+    // The next state depends only on how many cells in the neighbourhood are alive:
+    // bit k of `code` is the next state when k of them are
+    pub fn totalistic(radius: usize, code: u64) -> Rule {
+        let size = 1u32 << (2 * radius + 1);
+        Rule::new(
+            radius,
+            (0..size).map(|n| code >> n.count_ones() & 1 == 1).collect(),
+        )
+    }
+
+    pub fn radius(&self) -> usize {
+        self.radius
+    }
+
+    pub fn next_state(&self, neighbourhood: usize) -> bool {
+        self.table[neighbourhood]
+    }
+
+    // The state of all cells far away from anything else, after one generation
+    pub fn next_background(&self, background: bool) -> bool {
+        self.table[if background { self.table.len() - 1 } else { 0 }]
+    }
+}
+
+// A row of cells stretching infinitely both ways. All cells outside the `len` cells
+// starting at `offset` are in the same state, the background. The row is trimmed so
+// that its first and last cells differ from the background.
+//
+// The cells are packed into words, 64 cells to a word with the first cell in the
+// lowest bit. Bits beyond the last cell are set to the background.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Row {
+    pub background: bool,
+    pub offset: i64,
+    len: usize,
+    words: Vec<u64>,
+}
+
+fn fill(background: bool) -> u64 {
+    if background {
+        !0
+    } else {
+        0
+    }
+}
+
+// Sets the bits after the first `len` to the background
+fn fill_tail(words: &mut [u64], len: usize, background: bool) {
+    if !len.is_multiple_of(64) {
+        let used = (1 << (len % 64)) - 1;
+        let last = &mut words[len / 64];
+        *last = (*last & used) | (fill(background) & !used);
+    }
+}
+
+impl Row {
+    pub fn new(background: bool, offset: i64, cells: &[bool]) -> Row {
+        let mut words = vec![fill(background); cells.len().div_ceil(64)];
+        for (i, &cell) in cells.iter().enumerate() {
+            if cell {
+                words[i / 64] |= 1 << (i % 64);
+            } else {
+                words[i / 64] &= !(1 << (i % 64));
+            }
+        }
+        Row::trimmed(background, offset, cells.len(), words)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    // Whether every cell is in the background state
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, cell: i64) -> bool {
+        let i = cell - self.offset;
+        if 0 <= i && i < self.len as i64 {
+            self.words[i as usize / 64] >> (i % 64) & 1 == 1
+        } else {
+            self.background
+        }
+    }
+
+    // The cells from `offset`, as far as they differ from the background
+    pub fn cells(&self) -> Vec<bool> {
+        (0..self.len as i64)
+            .map(|i| self.get(self.offset + i))
+            .collect()
+    }
+
+    // The key under which this row's pattern is the same wherever it is in the row
+    pub fn pattern(&self) -> (bool, usize, &[u64]) {
+        (self.background, self.len, &self.words)
+    }
+
+    // Word number k of the cells, or the background outside of them
+    fn word(&self, k: i64) -> u64 {
+        if 0 <= k && k < self.words.len() as i64 {
+            self.words[k as usize]
+        } else {
+            fill(self.background)
+        }
+    }
+
+    // The 64 cells starting at cell number `start`, counting from `offset`
+    fn bits_from(&self, start: i64) -> u64 {
+        let (k, shift) = (start.div_euclid(64), start.rem_euclid(64));
+        if shift == 0 {
+            self.word(k)
+        } else {
+            (self.word(k) >> shift) | (self.word(k + 1) << (64 - shift))
+        }
+    }
+
+    // Builds a row from words, with `len` cells starting at `offset`, cutting off
+    // the cells at both ends that are in the background state
+    fn trimmed(background: bool, offset: i64, len: usize, mut words: Vec<u64>) -> Row {
+        let bg = fill(background);
+        fill_tail(&mut words, len, background);
+        let first = words
+            .iter()
+            .position(|&w| w != bg)
+            .map(|k| k * 64 + (words[k] ^ bg).trailing_zeros() as usize);
+        let last = words
+            .iter()
+            .rposition(|&w| w != bg)
+            .map(|k| k * 64 + 63 - (words[k] ^ bg).leading_zeros() as usize);
+        let (first, last) = match (first, last) {
+            (Some(first), Some(last)) => (first, last),
+            _ => {
+                return Row {
+                    background,
+                    offset: 0,
+                    len: 0,
+                    words: Vec::new(),
+                }
+            }
+        };
+
+        let untrimmed = Row {
+            background,
+            offset,
+            len,
+            words,
+        };
+        let len = last - first + 1;
+        let words = (0..len.div_ceil(64))
+            .map(|k| untrimmed.bits_from((first + 64 * k) as i64))
+            .collect();
+        let mut row = Row {
+            background,
+            offset: offset + first as i64,
+            len,
+            words,
+        };
+        fill_tail(&mut row.words, len, background);
+        row
+    }
+
+    // The next generation, 64 cells at a time. For each shift d from -radius to
+    // radius, take the word of cells d places to the right of the cells being
+    // computed. A cell's next state is alive if its neighbourhood matches one of the
+    // table entries that make a cell alive, and it matches an entry if every one of
+    // those words has the entry's bit in that position. When most entries make a
+    // cell alive, it's quicker to look for the others and invert the result.
+    pub fn step(&self, rule: &Rule) -> Row {
+        let r = rule.radius as i64;
+        let alive = rule.table.iter().filter(|&&s| s).count();
+        let look_for = alive <= rule.table.len() / 2;
+        let entries: Vec<usize> = (0..rule.table.len())
+            .filter(|&n| rule.table[n] == look_for)
+            .collect();
+
+        // Only the cells within the radius of this row can differ from the new
+        // background
+        let len = self.len + 2 * rule.radius;
+        let words = (0..len.div_ceil(64) as i64)
+            .map(|k| {
+                let neighbours: Vec<u64> =
+                    (-r..=r).map(|d| self.bits_from(64 * k - r + d)).collect();
+                let mut matched = 0;
+                for &n in &entries {
+                    let mut matches = !0;
+                    for (i, &word) in neighbours.iter().enumerate() {
+                        // The leftmost cell is the highest bit of the neighbourhood
+                        let bit = 2 * rule.radius - i;
+                        matches &= if n >> bit & 1 == 1 { word } else { !word };
+                    }
+                    matched |= matches;
+                }
+                if look_for {
+                    matched
+                } else {
+                    !matched
+                }
+            })
+            .collect();
+
+        let background = rule.next_background(self.background);
+        Row::trimmed(background, self.offset - r, len, words)
+    }
+
+    // The positions of the cells that are alive, if there are finitely many
+    pub fn alive(&self) -> Option<Vec<i64>> {
+        if self.background {
+            return None;
+        }
+        let mut cells = Vec::new();
+        for (k, &word) in self.words.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                cells.push(self.offset + 64 * k as i64 + word.trailing_zeros() as i64);
+                word &= word - 1;
+            }
+        }
+        Some(cells)
+    }
+}
+
+#[cfg(test)]
+fn step_slowly(row: &Row, rule: &Rule) -> Row {
+    let r = rule.radius() as i64;
+    let first = row.offset - r;
+    let cells: Vec<bool> = (first..row.offset + row.len() as i64 + r)
+        .map(|cell| {
+            let n = (-r..=r).fold(0, |n, d| n << 1 | row.get(cell + d) as usize);
+            rule.next_state(n)
+        })
+        .collect();
+    Row::new(rule.next_background(row.background), first, &cells)
+}
+
+#[test]
+fn test() {
+    let cells: Vec<bool> = "#..##.#...#####.#..#".chars().map(|c| c == '#').collect();
+    let row = Row::new(false, -3, &cells);
+    assert_eq!(row.cells(), cells);
+    assert_eq!(row.alive().unwrap()[..3], [-3, 0, 1]);
+
+    // Trimming
+    let row = Row::new(false, 10, &[false, false, true, false, true, false]);
+    assert_eq!((row.offset, row.cells()), (12, vec![true, false, true]));
+    let row = Row::new(true, 10, &[true, false, true, true]);
+    assert_eq!((row.offset, row.len()), (11, 1));
+    assert!(Row::new(false, 5, &[false; 100]).is_empty());
+
+    // Rule 90 draws Sierpinski triangles: row 2^k has only two cells alive
+    let mut row = Row::new(false, 0, &[true]);
+    for _ in 0..128 {
+        row = row.step(&Rule::elementary(90));
+    }
+    assert_eq!(row.alive(), Some(vec![-128, 128]));
+
+    let mut rules = vec![Rule::totalistic(2, 0b10110), Rule::totalistic(3, 0b1001010)];
+    rules.extend([30, 110, 1, 57, 73].iter().map(|&n| Rule::elementary(n)));
+    for rule in rules {
+        let mut fast = Row::new(false, 0, &cells);
+        let mut slow = fast.clone();
+        for _ in 0..200 {
+            fast = fast.step(&rule);
+            slow = step_slowly(&slow, &rule);
+            assert_eq!(fast, slow);
+        }
+    }
+}
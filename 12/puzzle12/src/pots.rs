@@ -0,0 +1,190 @@
+use crate::automaton::{Row, Rule};
+use regex::Regex;
+use std::collections::HashMap;
+
+// Rules are indexed by an integer representing the five pots "before", encoding
+// them as bits. So #..#. => # will be rules[18] == true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules(pub [bool; 32]);
+
+impl Rules {
+    // Rules that aren't mentioned leave the pot empty
+    pub fn parse<'a>(lines: impl Iterator<Item = &'a str>) -> Rules {
+        let mut rules = [false; 32];
+        let re = Regex::new(r"(?P<before>[#.]{5}) => (?P<after>[#.])").unwrap();
+        for caps in lines.filter_map(|s| re.captures(s)) {
+            // Compute the index by turning #/. into 1/0 and shifting it left.
+            let conf: usize = caps["before"]
+                .chars()
+                .enumerate()
+                .map(|(i, c)| ((c == '#') as usize) << (4 - i))
+                .fold(0, std::ops::BitOr::bitor);
+            rules[conf] = caps["after"].starts_with('#');
+        }
+        Rules(rules)
+    }
+
+    pub fn rule(&self) -> Rule {
+        Rule::new(2, self.0.to_vec())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PlantError {
+    // When ..... => #, all the empty pots far away grow plants
+    InfinitePlants { generation: u64 },
+    // The pattern of plants didn't repeat within this many generations
+    NoRepeat { searched: usize },
+}
+
+// How many generations plant_sum simulates at most, looking for a repeat
+const SEARCH_LIMIT: usize = 2_000;
+
+// Patterns that don't repeat are still simulated up to this many generations
+const SIMULATION_LIMIT: u64 = 10_000;
+
+// The first line is the starting state. Look only for . # characters.
+pub fn parse_row(s: &str) -> Row {
+    let cells: Vec<bool> = s
+        .chars()
+        .filter(|&c| c == '#' || c == '.')
+        .map(|c| c == '#')
+        .collect();
+    Row::new(false, 0, &cells)
+}
+
+// The sum of the numbers of all pots with plants, if there are finitely many
+fn sum(row: &Row) -> Option<i64> {
+    row.alive().map(|pots| pots.iter().sum())
+}
+
+// Steps the row the given number of generations, without looking for repeats
+fn simulate(row: &Row, rule: &Rule, steps: u64, generations: u64) -> Result<i64, PlantError> {
+    let infinite = PlantError::InfinitePlants {
+        generation: generations,
+    };
+    let mut row = row.clone();
+    for _ in 0..steps {
+        if row.background && rule.next_background(true) {
+            return Err(infinite);
+        }
+        row = row.step(rule);
+    }
+    sum(&row).ok_or(infinite)
+}
+
+// The sum of the numbers of all pots with plants after some generations, for any
+// automaton. Once the pattern of plants repeats, possibly moved along the row, later
+// generations follow from the ones since the pattern first appeared. A pattern that
+// hasn't repeated after SEARCH_LIMIT generations is simulated further if that takes
+// no more than SIMULATION_LIMIT generations, and is an error otherwise.
+pub fn plant_sum(initial: &Row, rule: &Rule, generations: u64) -> Result<i64, PlantError> {
+    let mut history = vec![initial.clone()];
+    let mut seen: HashMap<(bool, usize, Vec<u64>), usize> = HashMap::new();
+    loop {
+        let generation = history.len() - 1;
+        let row = &history[generation];
+        if generation as u64 == generations {
+            return sum(row).ok_or(PlantError::InfinitePlants {
+                generation: generations,
+            });
+        }
+        // Once the background is full of plants and stays that way, so are all
+        // later generations
+        if row.background && rule.next_background(true) {
+            return Err(PlantError::InfinitePlants {
+                generation: generations,
+            });
+        }
+        let (background, len, words) = row.pattern();
+        let pattern = (background, len, words.to_vec());
+        if let Some(&first) = seen.get(&pattern) {
+            let period = (generation - first) as u64;
+            let shift = row.offset - history[first].offset;
+            let remaining = generations - first as u64;
+            let same = &history[first + (remaining % period) as usize];
+            let plants = same.alive().ok_or(PlantError::InfinitePlants {
+                generation: generations,
+            })?;
+            let moved = (remaining / period) as i64 * shift;
+            return Ok(plants.iter().sum::<i64>() + moved * plants.len() as i64);
+        }
+        if generation == SEARCH_LIMIT {
+            if generations > SIMULATION_LIMIT {
+                return Err(PlantError::NoRepeat {
+                    searched: SEARCH_LIMIT,
+                });
+            }
+            return simulate(row, rule, generations - generation as u64, generations);
+        }
+        let next = row.step(rule);
+        seen.insert(pattern, generation);
+        history.push(next);
+    }
+}
+
+#[test]
+fn test() {
+    let input = include_str!("../../input2");
+    let initial = parse_row(input.lines().next().unwrap());
+    let rule = Rules::parse(input.lines().skip(1)).rule();
+
+    assert_eq!(plant_sum(&initial, &rule, 20), Ok(325));
+    let mut row = initial.clone();
+    for generation in 0..300 {
+        assert_eq!(
+            plant_sum(&initial, &rule, generation),
+            Ok(sum(&row).unwrap())
+        );
+        row = row.step(&rule);
+    }
+}
+
+#[test]
+fn test_background() {
+    let initial = parse_row("#..#.#");
+
+    // Empty pots fill up, and full ones empty, so the background alternates
+    let mut rules = Rules::parse("..... => #".lines());
+    let rule = rules.rule();
+    let mut row = initial.clone();
+    for generation in 0..100 {
+        let expected = match generation % 2 {
+            0 => Ok(sum(&row).unwrap()),
+            _ => Err(PlantError::InfinitePlants { generation }),
+        };
+        assert_eq!(plant_sum(&initial, &rule, generation), expected);
+        row = row.step(&rule);
+    }
+    assert_eq!(
+        plant_sum(&initial, &rule, 1_000_000_001),
+        Err(PlantError::InfinitePlants {
+            generation: 1_000_000_001
+        })
+    );
+
+    // Once full, pots stay full
+    rules.0[31] = true;
+    let rule = rules.rule();
+    assert_eq!(
+        plant_sum(&initial, &rule, 1_000_000_000),
+        Err(PlantError::InfinitePlants {
+            generation: 1_000_000_000
+        })
+    );
+    assert_eq!(plant_sum(&initial, &rule, 0), Ok(8));
+
+    // A pattern that keeps growing, like a Sierpinski triangle
+    let rule = Rule::totalistic(2, 0b10);
+    let mut row = initial.clone();
+    for _ in 0..2_500 {
+        row = row.step(&rule);
+    }
+    assert_eq!(plant_sum(&initial, &rule, 2_500), Ok(sum(&row).unwrap()));
+    assert_eq!(
+        plant_sum(&initial, &rule, 1_000_000_000),
+        Err(PlantError::NoRepeat {
+            searched: SEARCH_LIMIT
+        })
+    );
+}
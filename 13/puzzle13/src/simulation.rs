@@ -0,0 +1,355 @@
+use crate::track::{self, TrackError};
+use std::collections::VecDeque;
+use std::fmt;
+
+pub type Position = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollisionEvent {
+    // Counting from 1: a collision during the first tick happens at tick 1
+    pub tick: usize,
+    pub position: Position,
+    // The carts that crashed, numbered in the order they appear in the input
+    pub carts: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cart {
+    pub position: Position,
+    pub dir: Direction,
+    next_turn: RelativeDirection,
+    pub crashed: bool,
+}
+
+impl Cart {
+    fn new(position: Position, dir: Direction) -> Cart {
+        Cart {
+            position,
+            dir,
+            next_turn: RelativeDirection::Left,
+            crashed: false,
+        }
+    }
+
+    fn advance(&mut self) {
+        let (x, y) = &mut self.position;
+        match self.dir {
+            Direction::North => *y -= 1,
+            Direction::East => *x += 1,
+            Direction::South => *y += 1,
+            Direction::West => *x -= 1,
+        }
+    }
+
+    fn turn(&mut self, track: char) {
+        let rd = match (track, self.dir) {
+            ('/', Direction::North)
+            | ('/', Direction::South)
+            | ('\\', Direction::East)
+            | ('\\', Direction::West) => RelativeDirection::Right,
+            ('/', Direction::East)
+            | ('/', Direction::West)
+            | ('\\', Direction::North)
+            | ('\\', Direction::South) => RelativeDirection::Left,
+            ('+', _) => {
+                let d = self.next_turn;
+                self.next_turn = self.next_turn.next();
+                d
+            }
+            _ => RelativeDirection::Straight,
+        };
+        self.dir.add_to(rd);
+    }
+}
+
+// The carts on their tracks, moving one tick at a time. Crashed carts are removed
+// from the tracks at once, but keep their number.
+#[derive(Debug, Clone)]
+pub struct CartSimulation {
+    tracks: Vec<Vec<char>>,
+    carts: Vec<Cart>,
+    tick: usize,
+    // To notice when the carts go round in circles without ever meeting: the state of
+    // the carts at some earlier tick, and how many ticks ago that was. The state is
+    // saved again after 1, 2, 4, 8... ticks, so any cycle is found eventually (this
+    // is Brent's algorithm). Nothing changes a cycle but a collision.
+    saved: Vec<Cart>,
+    since_saved: usize,
+    save_after: usize,
+    repeating: bool,
+    // Collisions of the last tick that the iterator hasn't handed out yet
+    pending: VecDeque<CollisionEvent>,
+}
+
+impl CartSimulation {
+    pub fn new(tracks: Vec<Vec<char>>, carts: Vec<Cart>) -> CartSimulation {
+        CartSimulation {
+            tracks,
+            saved: carts.clone(),
+            carts,
+            tick: 0,
+            since_saved: 0,
+            save_after: 1,
+            repeating: false,
+            pending: VecDeque::new(),
+        }
+    }
+
+    // Collects all the carts, and works out the track underneath them
+    pub fn parse(input: &str) -> Result<CartSimulation, TrackError> {
+        let grid: Vec<Vec<char>> = input.lines().map(|l| l.chars().collect()).collect();
+        let tracks = track::validate(&grid)?;
+        let mut carts = Vec::new();
+        for (y, l) in grid.iter().enumerate() {
+            for (x, c) in l.iter().enumerate() {
+                if let Some(d) = Direction::try_from(c) {
+                    carts.push(Cart::new((x, y), d));
+                }
+            }
+        }
+        Ok(CartSimulation::new(tracks, carts))
+    }
+
+    pub fn tick(&self) -> usize {
+        self.tick
+    }
+
+    pub fn carts(&self) -> &[Cart] {
+        &self.carts
+    }
+
+    // The carts that haven't crashed, by number
+    pub fn remaining(&self) -> Vec<usize> {
+        (0..self.carts.len())
+            .filter(|&c| !self.carts[c].crashed)
+            .collect()
+    }
+
+    // Whether there will be no more collisions: when fewer than two carts are left,
+    // or when they're back in a state they were in before
+    pub fn finished(&self) -> bool {
+        self.remaining().len() < 2 || self.repeating
+    }
+
+    // Moves every cart once, starting at the top row and going left to right in each
+    // row. A cart that moves onto another crashes with it immediately, so a cart that
+    // is hit before its turn doesn't move.
+    pub fn step(&mut self) -> Vec<CollisionEvent> {
+        self.tick += 1;
+        let mut order = self.remaining();
+        order.sort_by_key(|&c| {
+            let (x, y) = self.carts[c].position;
+            (y, x)
+        });
+
+        let mut events = Vec::new();
+        for c in order {
+            if self.carts[c].crashed {
+                continue;
+            }
+            self.carts[c].advance();
+            let (x, y) = self.carts[c].position;
+            let track = self.tracks[y][x];
+            self.carts[c].turn(track);
+
+            let hit: Vec<usize> = (0..self.carts.len())
+                .filter(|&o| o != c && !self.carts[o].crashed && self.carts[o].position == (x, y))
+                .collect();
+            if !hit.is_empty() {
+                let mut carts = hit;
+                carts.push(c);
+                carts.sort_unstable();
+                for &crashed in &carts {
+                    self.carts[crashed].crashed = true;
+                }
+                events.push(CollisionEvent {
+                    tick: self.tick,
+                    position: (x, y),
+                    carts,
+                });
+            }
+        }
+
+        if !events.is_empty() {
+            self.saved = self.carts.clone();
+            self.since_saved = 0;
+            self.save_after = 1;
+        } else {
+            self.since_saved += 1;
+            if self.carts == self.saved {
+                self.repeating = true;
+            } else if self.since_saved == self.save_after {
+                self.saved = self.carts.clone();
+                self.since_saved = 0;
+                self.save_after *= 2;
+            }
+        }
+        events
+    }
+}
+
+// The track with the carts on it, and an X wherever carts have crashed
+impl fmt::Display for CartSimulation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut grid = self.tracks.clone();
+        for cart in self.carts.iter().filter(|c| c.crashed) {
+            let (x, y) = cart.position;
+            grid[y][x] = 'X';
+        }
+        for cart in self.carts.iter().filter(|c| !c.crashed) {
+            let (x, y) = cart.position;
+            grid[y][x] = match cart.dir {
+                Direction::North => '^',
+                Direction::East => '>',
+                Direction::South => 'v',
+                Direction::West => '<',
+            };
+        }
+        for row in grid {
+            writeln!(f, "{}", row.iter().collect::<String>().trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+// Runs the simulation to the end, giving every collision in order
+impl Iterator for CartSimulation {
+    type Item = CollisionEvent;
+
+    fn next(&mut self) -> Option<CollisionEvent> {
+        // Collisions in the same tick are handed out one by one
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.finished() {
+                return None;
+            }
+            let events = self.step();
+            self.pending.extend(events);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North = 0,
+    East = 1,
+    South = 2,
+    West = 3,
+}
+
+impl Direction {
+    fn add(self, rd: RelativeDirection) -> Direction {
+        From::from((self as i32 + rd as i32) % 4)
+    }
+    fn add_to(&mut self, rd: RelativeDirection) {
+        *self = self.add(rd);
+    }
+    pub(crate) fn try_from(c: &char) -> Option<Direction> {
+        match *c {
+            '^' => Some(Direction::North),
+            '>' => Some(Direction::East),
+            'v' => Some(Direction::South),
+            '<' => Some(Direction::West),
+            _ => None,
+        }
+    }
+}
+
+impl From<i32> for Direction {
+    fn from(n: i32) -> Direction {
+        match n {
+            0 => Direction::North,
+            1 => Direction::East,
+            2 => Direction::South,
+            _ => Direction::West, // also catches -1 from Direction::add :)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RelativeDirection {
+    Left = -1,
+    Straight = 0,
+    Right = 1,
+}
+
+impl RelativeDirection {
+    fn next(self) -> RelativeDirection {
+        From::from(self as i32 + 1)
+    }
+}
+
+impl From<i32> for RelativeDirection {
+    fn from(n: i32) -> RelativeDirection {
+        match n {
+            0 => RelativeDirection::Straight,
+            1 => RelativeDirection::Right,
+            _ => RelativeDirection::Left,
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let mut simulation = CartSimulation::parse(include_str!("../../input2")).unwrap();
+    let events: Vec<CollisionEvent> = simulation.by_ref().collect();
+    assert_eq!(
+        events,
+        vec![CollisionEvent {
+            tick: 14,
+            position: (7, 3),
+            carts: vec![0, 1],
+        }]
+    );
+    assert!(simulation.remaining().is_empty());
+
+    let mut simulation = CartSimulation::parse(include_str!("../../input3")).unwrap();
+    let events: Vec<(usize, Position)> =
+        simulation.by_ref().map(|e| (e.tick, e.position)).collect();
+    assert_eq!(
+        events,
+        vec![(1, (2, 0)), (1, (2, 4)), (1, (6, 4)), (3, (2, 4))]
+    );
+    assert_eq!(simulation.tick(), 3);
+    let last = simulation.remaining();
+    assert_eq!(last.len(), 1);
+    assert_eq!(simulation.carts()[last[0]].position, (6, 4));
+}
+
+#[test]
+fn test_render() {
+    let input = include_str!("../../input2");
+    let mut simulation = CartSimulation::parse(input).unwrap();
+    let trimmed: Vec<&str> = input.lines().map(str::trim_end).collect();
+    assert_eq!(simulation.to_string(), trimmed.join("\n") + "\n");
+
+    while simulation.tick() < 14 {
+        simulation.step();
+    }
+    let expected = [
+        r"/---\",
+        r"|   |  /----\",
+        r"| /-+--+-\  |",
+        r"| | |  X |  |",
+        r"\-+-/  \-+--/",
+        r"  \------/",
+    ];
+    assert_eq!(simulation.to_string(), expected.join("\n") + "\n");
+}
+
+#[test]
+fn test_no_crash() {
+    // Two carts going round separate loops never meet
+    let tracks = [r"/>-\ /--\", r"|  | |  |", r"\--/ \<-/"].join("\n");
+    let mut simulation = CartSimulation::parse(&tracks).unwrap();
+    assert_eq!(simulation.next(), None);
+    assert_eq!(simulation.remaining(), vec![0, 1]);
+
+    // Loops of 10 and 14 pieces only get back in the same state after 70 ticks
+    let tracks = [r"/>-\ /---\", r"|  | |   |", r"\--/ |   |", r"     \<--/"].join("\n");
+    let mut simulation = CartSimulation::parse(&tracks).unwrap();
+    assert_eq!(simulation.next(), None);
+    assert!(simulation.tick() >= 70);
+}
@@ -0,0 +1,198 @@
+// Working out which piece of track is under each cart, and checking that all the
+// track joins up.
+//
+// A piece of track is described by the sides it connects, one bit per side. Every
+// cell starts out with the pieces its character could be, and any piece that opens
+// towards a neighbour that can't open back (or that a neighbour needs but can't get)
+// is ruled out, until nothing changes any more.
+use crate::simulation::{Direction, Position};
+
+const NORTH: u8 = 1;
+const EAST: u8 = 2;
+const SOUTH: u8 = 4;
+const WEST: u8 = 8;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum TrackError {
+    // No piece of track fits in with the neighbours here
+    Disconnected {
+        position: Position,
+    },
+    // More than one piece of track would fit here
+    Ambiguous {
+        position: Position,
+        pieces: Vec<char>,
+    },
+}
+
+fn side(dir: Direction) -> u8 {
+    match dir {
+        Direction::North => NORTH,
+        Direction::East => EAST,
+        Direction::South => SOUTH,
+        Direction::West => WEST,
+    }
+}
+
+// The pieces a character could stand for
+fn candidates(c: char) -> Vec<u8> {
+    match c {
+        '|' => vec![NORTH | SOUTH],
+        '-' => vec![EAST | WEST],
+        '+' => vec![NORTH | EAST | SOUTH | WEST],
+        '/' => vec![SOUTH | EAST, NORTH | WEST],
+        '\\' => vec![SOUTH | WEST, NORTH | EAST],
+        '^' | '>' | 'v' | '<' => {
+            // A cart can be on anything that lets it go where it's facing
+            let facing = side(Direction::try_from(&c).unwrap());
+            [
+                NORTH | SOUTH,
+                EAST | WEST,
+                NORTH | EAST | SOUTH | WEST,
+                SOUTH | EAST,
+                NORTH | WEST,
+                SOUTH | WEST,
+                NORTH | EAST,
+            ]
+            .iter()
+            .copied()
+            .filter(|&p| p & facing != 0)
+            .collect()
+        }
+        _ => vec![0],
+    }
+}
+
+pub fn piece(sides: u8) -> char {
+    match sides {
+        0 => ' ',
+        s if s == NORTH | SOUTH => '|',
+        s if s == EAST | WEST => '-',
+        s if s == SOUTH | EAST || s == NORTH | WEST => '/',
+        s if s == SOUTH | WEST || s == NORTH | EAST => '\\',
+        _ => '+',
+    }
+}
+
+// The track with every cart replaced by the piece under it. Short lines are padded
+// with empty cells, so all rows have the same length.
+pub fn validate(grid: &[Vec<char>]) -> Result<Vec<Vec<char>>, TrackError> {
+    let height = grid.len();
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let mut options: Vec<Vec<Vec<u8>>> = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| candidates(grid[y].get(x).copied().unwrap_or(' ')))
+                .collect()
+        })
+        .collect();
+
+    // The neighbour on each side, with the side of the neighbour that faces back
+    let neighbours = |x: usize, y: usize| {
+        [
+            (NORTH, (y > 0).then(|| (x, y - 1)), SOUTH),
+            (EAST, (x + 1 < width).then_some((x + 1, y)), WEST),
+            (SOUTH, (y + 1 < height).then_some((x, y + 1)), NORTH),
+            (WEST, (x > 0).then(|| (x - 1, y)), EAST),
+        ]
+    };
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for y in 0..height {
+            for x in 0..width {
+                let fits = |p: u8| {
+                    neighbours(x, y).iter().all(|&(here, other, there)| {
+                        let open = p & here != 0;
+                        // A neighbour that doesn't fit anywhere is reported on its
+                        // own, and shouldn't take the cells around it along
+                        match other {
+                            Some((ox, oy)) => {
+                                let theirs = &options[oy][ox];
+                                theirs.is_empty()
+                                    || theirs.iter().any(|&q| (q & there != 0) == open)
+                            }
+                            None => !open,
+                        }
+                    })
+                };
+                let kept: Vec<u8> = options[y][x].iter().copied().filter(|&p| fits(p)).collect();
+                if kept.len() < options[y][x].len() {
+                    options[y][x] = kept;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut track = vec![vec![' '; width]; height];
+    for (y, row) in options.iter().enumerate() {
+        for (x, pieces) in row.iter().enumerate() {
+            match pieces[..] {
+                [] => return Err(TrackError::Disconnected { position: (x, y) }),
+                [p] => track[y][x] = piece(p),
+                _ => {
+                    return Err(TrackError::Ambiguous {
+                        position: (x, y),
+                        pieces: pieces.iter().map(|&p| piece(p)).collect(),
+                    })
+                }
+            }
+        }
+    }
+    Ok(track)
+}
+
+#[test]
+fn test() {
+    let grid =
+        |lines: &[&str]| -> Vec<Vec<char>> { lines.iter().map(|l| l.chars().collect()).collect() };
+
+    // Carts on a curve and on an intersection
+    let track = validate(&grid(&[
+        r"/---\",
+        r"|  />--\",
+        r"^  ||  |",
+        r"\--+/  |",
+        r"   \---/",
+    ]));
+    assert_eq!(
+        track,
+        Ok(grid(&[
+            r"/---\   ",
+            r"|  /+--\",
+            r"|  ||  |",
+            r"\--+/  |",
+            r"   \---/"
+        ]))
+    );
+    let track = validate(&grid(&[r">--\", r"|  |", r"\--/"]));
+    assert_eq!(track, Ok(grid(&[r"/--\", r"|  |", r"\--/"])));
+
+    // A dead end, and a curve that leads nowhere
+    assert_eq!(
+        validate(&grid(&[r"/--\", r"|  |", r"\- /"])),
+        Err(TrackError::Disconnected { position: (1, 2) })
+    );
+    assert_eq!(
+        validate(&grid(&[r"/--\ /", r"|  |", r"\--/"])),
+        Err(TrackError::Disconnected { position: (5, 0) })
+    );
+
+    // Curves that only have each other to go by
+    let track = validate(&grid(&[r"/\", r"\/"]));
+    assert_eq!(track, Ok(grid(&[r"/\", r"\/"])));
+    let track = validate(&grid(&[r"/>\", r"\-/"]));
+    assert_eq!(track, Ok(grid(&[r"/-\", r"\-/"])));
+
+    // Carts where two lines cross, or where two loops meet: there's no telling
+    let grid = grid(&[r"  /\", r"  ||", r"/-^^-\", r"\-vv-/", r"  ||", r"  \/"]);
+    assert_eq!(
+        validate(&grid),
+        Err(TrackError::Ambiguous {
+            position: (2, 2),
+            pieces: vec!['+', '/'],
+        })
+    );
+}
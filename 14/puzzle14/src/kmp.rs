@@ -0,0 +1,60 @@
+// Knuth-Morris-Pratt matching, one digit at a time, so the digits can be checked
+// as they are produced without keeping any window of them around.
+pub struct Matcher {
+    pattern: Vec<u8>,
+    // For each prefix of the pattern, the length of its longest proper prefix that
+    // is also a suffix: where to carry on after a mismatch
+    failure: Vec<usize>,
+    matched: usize,
+}
+
+impl Matcher {
+    pub fn new(pattern: &[u8]) -> Matcher {
+        let mut failure = vec![0; pattern.len()];
+        let mut k = 0;
+        for i in 1..pattern.len() {
+            while k > 0 && pattern[i] != pattern[k] {
+                k = failure[k - 1];
+            }
+            if pattern[i] == pattern[k] {
+                k += 1;
+            }
+            failure[i] = k;
+        }
+        Matcher {
+            pattern: pattern.to_vec(),
+            failure,
+            matched: 0,
+        }
+    }
+
+    // Takes the next digit, and tells whether the pattern ends with it
+    pub fn feed(&mut self, digit: u8) -> bool {
+        if self.pattern.is_empty() {
+            return true;
+        }
+        if self.matched == self.pattern.len() {
+            self.matched = self.failure[self.matched - 1];
+        }
+        while self.matched > 0 && digit != self.pattern[self.matched] {
+            self.matched = self.failure[self.matched - 1];
+        }
+        if digit == self.pattern[self.matched] {
+            self.matched += 1;
+        }
+        self.matched == self.pattern.len()
+    }
+}
+
+#[test]
+fn test() {
+    let text = [1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1];
+    let pattern = [1, 0, 1, 0, 1];
+    let mut matcher = Matcher::new(&pattern);
+    let ends: Vec<usize> = (0..text.len()).filter(|&i| matcher.feed(text[i])).collect();
+    let expected: Vec<usize> = (pattern.len() - 1..text.len())
+        .filter(|&i| text[i + 1 - pattern.len()..=i] == pattern)
+        .collect();
+    assert_eq!(ends, expected);
+    assert_eq!(ends, vec![4, 9, 11]);
+}
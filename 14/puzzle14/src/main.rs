@@ -1,17 +1,27 @@
+use std::env;
 use std::io;
 use std::io::BufRead;
 
+mod kmp;
+use kmp::Matcher;
+
+const USAGE: &str = "Usage: puzzle14 [--limit RECIPES] < input";
+
 fn main() {
-    let input = io::stdin()
-        .lock()
-        .lines()
-        .next()
-        .unwrap()
-        .unwrap()
-        .parse::<usize>()
-        .unwrap();
+    let mut limit = usize::MAX;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().expect(USAGE);
+        match arg.as_str() {
+            "--limit" => limit = value().parse().expect(USAGE),
+            _ => panic!("{}", USAGE),
+        }
+    }
 
-    let mut elflist = ElfList::with_length(input + 10);
+    let line = io::stdin().lock().lines().next().unwrap().unwrap();
+    let input = line.trim().parse::<usize>().unwrap();
+
+    let elflist = ElfList::with_length(input + 10);
     //part1
     println!(
         "{:?}",
@@ -21,25 +31,37 @@ fn main() {
             .collect::<String>()
     );
 
-    //part2
-    let input_digits = input
-        .to_string()
+    //part2. Use the digits as written, leading zeros and all
+    let input_digits = line
+        .trim()
         .chars()
-        .map(|c| c.to_string().parse().unwrap())
+        .map(|c| c.to_digit(10).unwrap() as u8)
         .collect::<Vec<u8>>();
-    let mut start_from = 0;
-    loop {
-        elflist.list[start_from..]
-            .windows(input_digits.len())
-            .enumerate()
-            .for_each(|(i, w)| {
-                if w.to_vec() == input_digits {
-                    println!("Input occurs at {:?}", start_from + i);
-                }
-            });
-        start_from = elflist.list.len() - input_digits.len();
-        elflist.extend(1000);
+    match first_occurrence(&input_digits, limit) {
+        Some(i) => println!("Input occurs at {:?}", i),
+        None => println!("Input does not occur in the first {:?} recipes", limit),
+    }
+}
+
+// How many recipes come before the first time the pattern shows up, looking at no
+// more than `limit` recipes. The scoreboard never grows much beyond that.
+fn first_occurrence(pattern: &[u8], limit: usize) -> Option<usize> {
+    if pattern.is_empty() {
+        return Some(0);
     }
+    let mut matcher = Matcher::new(pattern);
+    let mut elflist = ElfList::new();
+    let mut seen = 0;
+    while seen < limit {
+        if seen == elflist.list.len() {
+            elflist.step();
+        }
+        if matcher.feed(elflist.list[seen]) {
+            return Some(seen + 1 - pattern.len());
+        }
+        seen += 1;
+    }
+    None
 }
 
 struct ElfList {
@@ -64,15 +86,34 @@ impl ElfList {
     fn extend(&mut self, by: usize) {
         let target = self.list.len() + by;
         while self.list.len() < target {
-            let (score1, score2) = (self.list[self.elf1], self.list[self.elf2]);
-            let mut total = score1 + score2;
-            if total > 9 {
-                self.list.push(total / 10);
-                total = total % 10;
-            }
-            self.list.push(total);
-            self.elf1 = (self.elf1 + score1 as usize + 1) % self.list.len();
-            self.elf2 = (self.elf2 + score2 as usize + 1) % self.list.len();
+            self.step();
+        }
+    }
+    // Makes new recipes once, adding one or two of them
+    fn step(&mut self) {
+        let (score1, score2) = (self.list[self.elf1], self.list[self.elf2]);
+        let mut total = score1 + score2;
+        if total > 9 {
+            self.list.push(total / 10);
+            total %= 10;
         }
+        self.list.push(total);
+        self.elf1 = (self.elf1 + score1 as usize + 1) % self.list.len();
+        self.elf2 = (self.elf2 + score2 as usize + 1) % self.list.len();
     }
 }
+
+#[test]
+fn test() {
+    let digits = |s: &str| -> Vec<u8> { s.bytes().map(|b| b - b'0').collect() };
+    assert_eq!(first_occurrence(&digits("51589"), usize::MAX), Some(9));
+    assert_eq!(first_occurrence(&digits("01245"), usize::MAX), Some(5));
+    assert_eq!(first_occurrence(&digits("92510"), usize::MAX), Some(18));
+    assert_eq!(first_occurrence(&digits("59414"), usize::MAX), Some(2018));
+    assert_eq!(first_occurrence(&digits("37"), usize::MAX), Some(0));
+    assert_eq!(first_occurrence(&digits(""), usize::MAX), Some(0));
+
+    // The match has to end within the limit
+    assert_eq!(first_occurrence(&digits("59414"), 2023), Some(2018));
+    assert_eq!(first_occurrence(&digits("59414"), 2022), None);
+}